@@ -0,0 +1,54 @@
+//! Constant product (x * y = k) math, without fees and with floor division, as used
+//! by every experiment so far. `k` is always computed in u128. Other curves and fees are
+//! [`Pool`] models, see [`pool`].
+
+pub mod clmm;
+pub mod constant_product;
+pub mod front_run;
+pub mod pool;
+pub mod stable_swap;
+
+pub use clmm::Clmm;
+pub use constant_product::{ConstantProduct, RaydiumV4};
+pub use front_run::max_front_run;
+pub use pool::{after_swap, pool_by_name, Pool};
+pub use stable_swap::StableSwap;
+
+/// Swaps `input_amount` in, returns `(new_balance_x, new_balance_y, output)`.
+pub fn swap(is_swap_x_to_y: bool, balance_x: u64, balance_y: u64, input_amount: u64) -> (u64, u64, u64) {
+    if is_swap_x_to_y {
+        let (new_balance_x, new_balance_y) = fake_trade_to_y(balance_x, balance_y, input_amount);
+        (new_balance_x, new_balance_y, balance_y - new_balance_y)
+    } else {
+        let (new_balance_x, new_balance_y) = fake_trade_to_x(balance_x, balance_y, input_amount);
+        (new_balance_x, new_balance_y, balance_x - new_balance_x)
+    }
+}
+
+/// Trades x in for y, returns the new `(balance_x, balance_y)`.
+pub fn fake_trade_to_y(balance_x: u64, balance_y: u64, input_amount: u64) -> (u64, u64) {
+    let k = balance_x as u128 * balance_y as u128;
+
+    let new_balance_x = balance_x + input_amount;
+    let new_balance_y = (k / new_balance_x as u128) as u64;
+
+    (new_balance_x, new_balance_y)
+}
+
+/// Trades y in for x, returns the new `(balance_x, balance_y)`.
+pub fn fake_trade_to_x(balance_x: u64, balance_y: u64, input_amount: u64) -> (u64, u64) {
+    let k = balance_x as u128 * balance_y as u128;
+
+    let new_balance_y = balance_y + input_amount;
+    let new_balance_x = (k / new_balance_y as u128) as u64;
+
+    (new_balance_x, new_balance_y)
+}
+
+/// Returns the `(upper, lower)` output boundaries around `output`.
+/// slippage = 10,000 = 100%, the upper boundary saturates at `u64::MAX` and the lower at 0.
+pub fn slippage_bounds(output: u64, slippage: u64) -> (u64, u64) {
+    let delta = (output as u128 * slippage as u128 / 10000).min(u64::MAX as u128) as u64;
+
+    (output.saturating_add(delta), output.saturating_sub(delta))
+}
@@ -0,0 +1,442 @@
+//! Concentrated liquidity (Uniswap v3 style): the price is a Q64.96 square root, liquidity
+//! is provided per tick range and a swap walks from one initialized tick to the next, so
+//! the output depends on which ticks it crosses rather than on `k / new_balance`.
+//!
+//! Tick math and swap steps follow Uniswap v3 `TickMath`, `SqrtPriceMath` and `SwapMath`
+//! with their rounding (inputs rounded up, outputs down). x is token0 and y token1, so
+//! x -> y is `zeroForOne` and lowers the price. Products go through `BigUint` where the
+//! contracts use 256 bit words.
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use std::collections::BTreeMap;
+
+use super::pool::{swapped_balances, Pool, BPS};
+
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+
+/// Fee denominator of the swap math, 1,000,000 = 100%.
+const PIPS: u64 = 1_000_000;
+
+/// `2^128 / sqrt(1.0001)^(2^k)` for every bit `k` of a tick, rounded up, from `TickMath`.
+const TICK_FACTORS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// Position widths of [`Clmm::ladder`] for [`pool_by_name`](super::pool_by_name), in tick spacings.
+pub const DEFAULT_LADDER: [i32; 4] = [1, 4, 16, 64];
+
+fn q96() -> BigUint {
+    BigUint::one() << 96
+}
+
+/// `sqrt(1.0001^tick)` as Q64.96, `getSqrtRatioAtTick`.
+pub fn sqrt_price_at_tick(tick: i32) -> BigUint {
+    assert!((MIN_TICK..=MAX_TICK).contains(&tick), "tick out of range");
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 1 != 0 { BigUint::from(TICK_FACTORS[0]) } else { BigUint::one() << 128 };
+
+    for (bit, &factor) in TICK_FACTORS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * factor) >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = ((BigUint::one() << 256) - 1u32) / ratio;
+    }
+
+    // Q128.128 to Q64.96, rounded up
+    let round_up = !(&ratio & BigUint::from(u32::MAX)).is_zero();
+    (ratio >> 32) + round_up as u32
+}
+
+/// Largest tick whose sqrt price is at most `sqrt_price_x96`, `getTickAtSqrtRatio`.
+pub fn tick_at_sqrt_price(sqrt_price_x96: &BigUint) -> i32 {
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+
+        if sqrt_price_at_tick(middle) <= *sqrt_price_x96 {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    low
+}
+
+fn div_up(numerator: BigUint, denominator: &BigUint) -> BigUint {
+    (numerator + denominator - 1u32) / denominator
+}
+
+fn mul_div_up(a: &BigUint, b: &BigUint, denominator: &BigUint) -> BigUint {
+    div_up(a * b, denominator)
+}
+
+/// x between two sqrt prices for `liquidity`, `getAmount0Delta`.
+fn amount_x_delta(a: &BigUint, b: &BigUint, liquidity: u128, round_up: bool) -> BigUint {
+    let (lower, upper) = if a < b { (a, b) } else { (b, a) };
+    let numerator = BigUint::from(liquidity) << 96;
+    let difference = upper - lower;
+
+    if round_up {
+        div_up(mul_div_up(&numerator, &difference, upper), lower)
+    } else {
+        numerator * difference / upper / lower
+    }
+}
+
+/// y between two sqrt prices for `liquidity`, `getAmount1Delta`.
+fn amount_y_delta(a: &BigUint, b: &BigUint, liquidity: u128, round_up: bool) -> BigUint {
+    let (lower, upper) = if a < b { (a, b) } else { (b, a) };
+    let product = BigUint::from(liquidity) * (upper - lower);
+
+    if round_up {
+        div_up(product, &q96())
+    } else {
+        product >> 96
+    }
+}
+
+/// Sqrt price once `amount` is added, `getNextSqrtPriceFromInput`. x in rounds the price
+/// up, y in rounds it down, so the pool never gives out more than it received.
+fn next_sqrt_price_from_input(sqrt_price: &BigUint, liquidity: u128, amount: &BigUint, is_swap_x_to_y: bool) -> BigUint {
+    if amount.is_zero() {
+        return sqrt_price.clone();
+    }
+
+    if is_swap_x_to_y {
+        let numerator = BigUint::from(liquidity) << 96;
+        let denominator = &numerator + amount * sqrt_price;
+        mul_div_up(&numerator, sqrt_price, &denominator)
+    } else {
+        sqrt_price + (amount << 96) / liquidity
+    }
+}
+
+/// One `computeSwapStep` towards `target` within constant liquidity.
+struct SwapStep {
+    sqrt_price: BigUint,
+    input: BigUint,
+    output: BigUint,
+    fee: BigUint,
+}
+
+fn swap_step(sqrt_price: &BigUint, target: &BigUint, liquidity: u128, remaining: &BigUint, fee_pips: u64) -> SwapStep {
+    let is_swap_x_to_y = sqrt_price >= target;
+    let remaining_less_fee = remaining * (PIPS - fee_pips) / PIPS;
+
+    let input_delta = |to: &BigUint| {
+        if is_swap_x_to_y {
+            amount_x_delta(to, sqrt_price, liquidity, true)
+        } else {
+            amount_y_delta(sqrt_price, to, liquidity, true)
+        }
+    };
+
+    let input_to_target = input_delta(target);
+    let next = if remaining_less_fee >= input_to_target {
+        target.clone()
+    } else {
+        next_sqrt_price_from_input(sqrt_price, liquidity, &remaining_less_fee, is_swap_x_to_y)
+    };
+    let is_at_target = next == *target;
+
+    let input = if is_at_target { input_to_target } else { input_delta(&next) };
+    let output = if is_swap_x_to_y {
+        amount_y_delta(&next, sqrt_price, liquidity, false)
+    } else {
+        amount_x_delta(sqrt_price, &next, liquidity, false)
+    };
+    // a step that stops short of the target takes the rest of the input as fee
+    let fee = if is_at_target {
+        mul_div_up(&input, &BigUint::from(fee_pips), &BigUint::from(PIPS - fee_pips))
+    } else {
+        remaining - &input
+    };
+
+    SwapStep { sqrt_price: next, input, output, fee }
+}
+
+/// Result of [`Clmm::simulate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClmmSwap {
+    pub output: u64,
+    /// Part of the input kept as fee.
+    pub fee: u64,
+    pub ticks_crossed: u32,
+    pub sqrt_price_x96: BigUint,
+    pub tick: i32,
+    pub liquidity: u128,
+}
+
+/// Concentrated liquidity pool. Balances are what the pool holds: the deposits of every
+/// position plus the swaps since.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clmm {
+    pub sqrt_price_x96: BigUint,
+    pub tick: i32,
+    /// Liquidity of the positions in range of the current price.
+    pub liquidity: u128,
+    /// 10,000 = 100%
+    pub fee_bps: u64,
+    pub tick_spacing: i32,
+    /// Liquidity added when the price crosses each initialized tick upwards.
+    pub ticks: BTreeMap<i32, i128>,
+    pub balance_x: u64,
+    pub balance_y: u64,
+}
+
+impl Clmm {
+    /// Pool without liquidity at `sqrt_price_x96`.
+    pub fn new(sqrt_price_x96: BigUint, fee_bps: u64, tick_spacing: i32) -> Self {
+        Clmm {
+            tick: tick_at_sqrt_price(&sqrt_price_x96),
+            sqrt_price_x96,
+            liquidity: 0,
+            fee_bps,
+            tick_spacing,
+            ticks: BTreeMap::new(),
+            balance_x: 0,
+            balance_y: 0,
+        }
+    }
+
+    /// Pool at the price `balance_y / balance_x` holding about those balances, spread
+    /// evenly over positions about `widths[k]` tick spacings either side of the current tick.
+    /// Wide positions are the deep liquidity far from the price, narrow ones are crossed
+    /// by any sizeable trade. `None` for an empty side or a price out of tick range.
+    pub fn ladder(balance_x: u64, balance_y: u64, fee_bps: u64, tick_spacing: i32, widths: &[i32]) -> Option<Self> {
+        if balance_x == 0 || balance_y == 0 {
+            return None;
+        }
+
+        let sqrt_price = (balance_y as f64 / balance_x as f64).sqrt();
+        let sqrt_price_x96 = BigUint::from((sqrt_price * 2f64.powi(64)) as u128) << 32;
+
+        if sqrt_price_x96 < sqrt_price_at_tick(MIN_TICK) || sqrt_price_x96 >= sqrt_price_at_tick(MAX_TICK) {
+            return None;
+        }
+
+        let mut pool = Clmm::new(sqrt_price_x96, fee_bps, tick_spacing);
+        let share = balance_x as f64 / widths.len() as f64;
+
+        // outermost usable ticks
+        let max_tick = MAX_TICK / tick_spacing * tick_spacing;
+
+        for &width in widths {
+            // rounded outwards to the spacing, so the price stays about in the middle
+            let lower = ((pool.tick - width * tick_spacing).div_euclid(tick_spacing) * tick_spacing).max(-max_tick);
+            let upper = (-(-pool.tick - width * tick_spacing).div_euclid(tick_spacing) * tick_spacing).min(max_tick);
+            let sqrt_upper = 1.0001f64.powf(upper as f64 / 2.0);
+
+            // x held by one unit of liquidity between the price and the upper tick
+            let liquidity = share / (1.0 / sqrt_price - 1.0 / sqrt_upper);
+            pool.add_liquidity(lower, upper, liquidity as u128)?;
+        }
+
+        Some(pool)
+    }
+
+    /// Adds a position between two initialized ticks and deposits its amounts, rounded up.
+    /// Returns the deposited `(x, y)`, `None` if a balance or the liquidity would overflow.
+    pub fn add_liquidity(&mut self, tick_lower: i32, tick_upper: i32, liquidity: u128) -> Option<(u64, u64)> {
+        assert!(tick_lower < tick_upper, "tick_lower must be below tick_upper");
+        assert!(tick_lower >= MIN_TICK && tick_upper <= MAX_TICK, "ticks out of range");
+        assert!(tick_lower % self.tick_spacing == 0 && tick_upper % self.tick_spacing == 0, "ticks must be multiples of the tick spacing");
+
+        let (sqrt_lower, sqrt_upper) = (sqrt_price_at_tick(tick_lower), sqrt_price_at_tick(tick_upper));
+        let sqrt_price = &self.sqrt_price_x96;
+
+        let (amount_x, amount_y) = if self.tick < tick_lower {
+            (amount_x_delta(&sqrt_lower, &sqrt_upper, liquidity, true), BigUint::zero())
+        } else if self.tick < tick_upper {
+            (amount_x_delta(sqrt_price, &sqrt_upper, liquidity, true), amount_y_delta(&sqrt_lower, sqrt_price, liquidity, true))
+        } else {
+            (BigUint::zero(), amount_y_delta(&sqrt_lower, &sqrt_upper, liquidity, true))
+        };
+
+        let amount_x = amount_x.to_u64()?;
+        let amount_y = amount_y.to_u64()?;
+        let balance_x = self.balance_x.checked_add(amount_x)?;
+        let balance_y = self.balance_y.checked_add(amount_y)?;
+
+        // near MIN_TICK the amounts fit a u64 for any liquidity, so it is bounded here
+        let net = i128::try_from(liquidity).ok()?;
+        let lower_net = self.ticks.get(&tick_lower).copied().unwrap_or(0).checked_add(net)?;
+        let upper_net = self.ticks.get(&tick_upper).copied().unwrap_or(0).checked_sub(net)?;
+        let in_range = if (tick_lower..tick_upper).contains(&self.tick) { self.liquidity.checked_add(liquidity)? } else { self.liquidity };
+
+        self.ticks.insert(tick_lower, lower_net);
+        self.ticks.insert(tick_upper, upper_net);
+        self.liquidity = in_range;
+        (self.balance_x, self.balance_y) = (balance_x, balance_y);
+
+        Some((amount_x, amount_y))
+    }
+
+    /// Walks the swap tick by tick without changing the pool. `None` if the liquidity
+    /// runs out before the whole input is used.
+    pub fn simulate(&self, is_swap_x_to_y: bool, input_amount: u64) -> Option<ClmmSwap> {
+        let fee_pips = self.fee_bps.min(BPS - 1) * (PIPS / BPS);
+
+        let mut remaining = BigUint::from(input_amount);
+        let mut output = BigUint::zero();
+        let mut fee = BigUint::zero();
+        let mut sqrt_price = self.sqrt_price_x96.clone();
+        let mut tick = self.tick;
+        let mut liquidity = self.liquidity;
+        let mut ticks_crossed = 0;
+
+        while !remaining.is_zero() {
+            // moving down the current tick is crossed too, moving up only the ones above it
+            let next = if is_swap_x_to_y {
+                self.ticks.range(..=tick).next_back()
+            } else {
+                self.ticks.range(tick + 1..).next()
+            };
+            let target = sqrt_price_at_tick(next.map_or(if is_swap_x_to_y { MIN_TICK } else { MAX_TICK }, |(&tick, _)| tick));
+
+            let step = swap_step(&sqrt_price, &target, liquidity, &remaining, fee_pips);
+            remaining -= &step.input + &step.fee;
+            output += step.output;
+            fee += step.fee;
+
+            if step.sqrt_price == target {
+                let (&crossed, &net) = next?;
+
+                let net = if is_swap_x_to_y { -net } else { net };
+                liquidity = liquidity.checked_add_signed(net)?;
+                tick = if is_swap_x_to_y { crossed - 1 } else { crossed };
+                ticks_crossed += 1;
+            } else {
+                tick = tick_at_sqrt_price(&step.sqrt_price);
+            }
+
+            sqrt_price = step.sqrt_price;
+        }
+
+        Some(ClmmSwap { output: output.to_u64()?, fee: fee.to_u64()?, ticks_crossed, sqrt_price_x96: sqrt_price, tick, liquidity })
+    }
+}
+
+impl Pool for Clmm {
+    fn name(&self) -> &'static str {
+        "clmm"
+    }
+
+    fn balances(&self) -> (u64, u64) {
+        (self.balance_x, self.balance_y)
+    }
+
+    fn quote_exact_in(&self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        Some(self.simulate(is_swap_x_to_y, input_amount)?.output)
+    }
+
+    fn apply_swap(&mut self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let swap = self.simulate(is_swap_x_to_y, input_amount)?;
+        (self.balance_x, self.balance_y) = swapped_balances(self.balances(), is_swap_x_to_y, input_amount, swap.output)?;
+
+        self.sqrt_price_x96 = swap.sqrt_price_x96;
+        self.tick = swap.tick;
+        self.liquidity = swap.liquidity;
+
+        Some(swap.output)
+    }
+
+    fn spot_price(&self) -> f64 {
+        let sqrt_price = self.sqrt_price_x96.to_f64().unwrap_or(f64::INFINITY) / 2f64.powi(96);
+
+        sqrt_price * sqrt_price
+    }
+
+    fn tick(&self) -> Option<i32> {
+        Some(self.tick)
+    }
+
+    fn clone_box(&self) -> Box<dyn Pool> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_price_matches_tick_math_constants() {
+        // MIN_SQRT_RATIO and MAX_SQRT_RATIO of TickMath
+        assert_eq!(sqrt_price_at_tick(MIN_TICK), BigUint::from(4295128739u64));
+        assert_eq!(sqrt_price_at_tick(MAX_TICK), "1461446703485210103287273052203988822378723970342".parse::<BigUint>().unwrap());
+        assert_eq!(sqrt_price_at_tick(0), q96());
+    }
+
+    #[test]
+    fn tick_round_trips() {
+        for tick in [MIN_TICK, MIN_TICK + 1, -100_000, -166, -1, 0, 1, 100, 50_000, MAX_TICK - 1, MAX_TICK] {
+            let sqrt_price = sqrt_price_at_tick(tick);
+
+            assert_eq!(tick_at_sqrt_price(&sqrt_price), tick);
+            if tick > MIN_TICK {
+                assert_eq!(tick_at_sqrt_price(&(sqrt_price - 1u32)), tick - 1);
+            }
+        }
+    }
+
+    /// Positions over [-100, 100] and [-200, 200] at tick 0 without a fee. The swap uses up
+    /// both down to tick -100, then continues in the wider one alone. By the textbook
+    /// formulas (L / sqrt(p) for x, L * sqrt(p) for y) the output is 29768592794.16, the
+    /// contracts' rounding (inputs up, outputs down per step) gives 2 less.
+    #[test]
+    fn swap_crosses_a_tick() {
+        let mut pool = Clmm::new(sqrt_price_at_tick(0), 0, 10);
+        pool.add_liquidity(-100, 100, 1_000_000_000_000).unwrap();
+        pool.add_liquidity(-200, 200, 3_000_000_000_000).unwrap();
+        assert_eq!(pool.balances(), (34_836_285_848, 34_836_285_848));
+
+        let swap = pool.simulate(true, 30_000_000_000).unwrap();
+
+        assert_eq!(swap.output, 29_768_592_792);
+        assert_eq!(swap.fee, 0);
+        assert_eq!(swap.ticks_crossed, 1);
+        assert_eq!(swap.tick, -166);
+        assert_eq!(swap.liquidity, 3_000_000_000_000);
+    }
+
+    #[test]
+    fn liquidity_above_i128_is_rejected() {
+        let mut pool = Clmm::new(sqrt_price_at_tick(0), 30, 1);
+
+        // the y deposit of a one tick position at MIN_TICK fits a u64 even for u128::MAX
+        assert_eq!(pool.add_liquidity(MIN_TICK, MIN_TICK + 1, u128::MAX), None);
+        assert_eq!(pool, Clmm::new(sqrt_price_at_tick(0), 30, 1));
+
+        // the tick's net liquidity would overflow
+        pool.add_liquidity(MIN_TICK, MIN_TICK + 1, i128::MAX as u128).unwrap();
+        assert_eq!(pool.add_liquidity(MIN_TICK, MIN_TICK + 1, 1), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! `x * y = k` pools: a generic one with a fee in basis points and Raydium AMM v4.
+
+use super::pool::{swapped_balances, Pool, BPS};
+
+/// `(balance_in, balance_out)` for the direction.
+pub(crate) fn oriented(pool: &dyn Pool, is_swap_x_to_y: bool) -> (u128, u128) {
+    let (balance_x, balance_y) = pool.balances();
+
+    if is_swap_x_to_y {
+        (balance_x as u128, balance_y as u128)
+    } else {
+        (balance_y as u128, balance_x as u128)
+    }
+}
+
+/// Largest front-run `f` keeping the victim's output at `min_output` for a constant product
+/// pool charging `1 - gamma` of the input, in real numbers. The front-run leaves
+/// `(x + f, x y / (x + gamma f))`, so the victim receives `x y gamma a / ((x + gamma f)(x + f + gamma a))`
+/// and `f` is the positive root of `gamma f^2 + (x + gamma x + gamma^2 a) f + x (x + gamma a) = x y gamma a / min_output`.
+fn constant_product_front_run(balance_in: u128, balance_out: u128, gamma: f64, input_amount: u64, min_output: u64) -> Option<f64> {
+    let (x, y, a, m) = (balance_in as f64, balance_out as f64, input_amount as f64, min_output.max(1) as f64);
+
+    let b = x + gamma * x + gamma * gamma * a;
+    // root - K, negative if even no front-run keeps the output
+    let c = x * (x + gamma * a) - x * y * gamma * a / m;
+
+    if gamma <= 0.0 || c > 0.0 {
+        return None;
+    }
+
+    // the stable form of the root, f is small next to b
+    Some(-2.0 * c / (b + (b * b - 4.0 * gamma * c).sqrt()))
+}
+
+/// Constant product with the fee taken from the input. The new output balance is
+/// `floor(k / (balance_in + input after fee))` like [`swap`](crate::amm::swap), which this
+/// matches exactly at 0 fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstantProduct {
+    pub balance_x: u64,
+    pub balance_y: u64,
+    /// 10,000 = 100%
+    pub fee_bps: u64,
+}
+
+impl ConstantProduct {
+    /// Fee-less pool.
+    pub fn new(balance_x: u64, balance_y: u64) -> Self {
+        ConstantProduct { balance_x, balance_y, fee_bps: 0 }
+    }
+}
+
+impl Pool for ConstantProduct {
+    fn name(&self) -> &'static str {
+        "constant_product"
+    }
+
+    fn balances(&self) -> (u64, u64) {
+        (self.balance_x, self.balance_y)
+    }
+
+    fn apply_swap(&mut self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let output = self.quote_exact_in(is_swap_x_to_y, input_amount)?;
+        (self.balance_x, self.balance_y) = swapped_balances(self.balances(), is_swap_x_to_y, input_amount, output)?;
+
+        Some(output)
+    }
+
+    fn quote_exact_in(&self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let (balance_in, balance_out) = oriented(self, is_swap_x_to_y);
+        let input_after_fee = input_amount as u128 * BPS.checked_sub(self.fee_bps)? as u128 / BPS as u128;
+
+        let new_balance_in = balance_in + input_after_fee;
+        let new_balance_out = (balance_in * balance_out).checked_div(new_balance_in)?;
+
+        Some((balance_out - new_balance_out) as u64)
+    }
+
+    fn spot_price(&self) -> f64 {
+        self.balance_y as f64 / self.balance_x as f64
+    }
+
+    fn front_run_estimate(&self, is_swap_x_to_y: bool, input_amount: u64, min_output: u64) -> Option<f64> {
+        let (balance_in, balance_out) = oriented(self, is_swap_x_to_y);
+        let gamma = BPS.checked_sub(self.fee_bps)? as f64 / BPS as f64;
+
+        constant_product_front_run(balance_in, balance_out, gamma, input_amount, min_output)
+    }
+
+    fn with_balances(&self, balance_x: u64, balance_y: u64) -> Option<Box<dyn Pool>> {
+        Some(Box::new(ConstantProduct { balance_x, balance_y, ..*self }))
+    }
+
+    fn clone_box(&self) -> Box<dyn Pool> {
+        Box::new(*self)
+    }
+}
+
+/// Raydium AMM v4 `swap_base_in`/`swap_base_out` math. The trade fee is rounded up and
+/// taken from the input, the exact-in output is floored, the exact-out input is rounded
+/// up before and after adding the fee. The balances stand for the vaults net of the
+/// PnL the program has not taken yet, which this model does not track.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaydiumV4 {
+    pub balance_x: u64,
+    pub balance_y: u64,
+    trade_fee_numerator: u64,
+    trade_fee_denominator: u64,
+}
+
+impl RaydiumV4 {
+    /// Pool with the default 25/10,000 trade fee.
+    pub fn new(balance_x: u64, balance_y: u64) -> Self {
+        RaydiumV4 { balance_x, balance_y, trade_fee_numerator: 25, trade_fee_denominator: 10_000 }
+    }
+
+    /// Pool with a `numerator / denominator` trade fee, `None` for a denominator of 0 or
+    /// a fee of 100% or more, which the quotes would divide by.
+    pub fn with_trade_fee(balance_x: u64, balance_y: u64, numerator: u64, denominator: u64) -> Option<Self> {
+        (numerator < denominator).then_some(RaydiumV4 { balance_x, balance_y, trade_fee_numerator: numerator, trade_fee_denominator: denominator })
+    }
+
+    /// `(numerator, denominator)`
+    pub fn trade_fee(&self) -> (u64, u64) {
+        (self.trade_fee_numerator, self.trade_fee_denominator)
+    }
+}
+
+impl Pool for RaydiumV4 {
+    fn name(&self) -> &'static str {
+        "raydium_v4"
+    }
+
+    fn balances(&self) -> (u64, u64) {
+        (self.balance_x, self.balance_y)
+    }
+
+    fn apply_swap(&mut self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let output = self.quote_exact_in(is_swap_x_to_y, input_amount)?;
+        (self.balance_x, self.balance_y) = swapped_balances(self.balances(), is_swap_x_to_y, input_amount, output)?;
+
+        Some(output)
+    }
+
+    fn quote_exact_in(&self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let (balance_in, balance_out) = oriented(self, is_swap_x_to_y);
+
+        let fee = (input_amount as u128 * self.trade_fee_numerator as u128).div_ceil(self.trade_fee_denominator as u128);
+        let input_after_fee = (input_amount as u128).checked_sub(fee)?;
+
+        Some((balance_out * input_after_fee).checked_div(balance_in + input_after_fee)? as u64)
+    }
+
+    fn quote_exact_out(&self, is_swap_x_to_y: bool, output_amount: u64) -> Option<u64> {
+        let (balance_in, balance_out) = oriented(self, is_swap_x_to_y);
+
+        let remaining = balance_out.checked_sub(output_amount as u128).filter(|&remaining| remaining > 0)?;
+        let input = (balance_in * output_amount as u128).div_ceil(remaining);
+
+        let fee_denominator = self.trade_fee_denominator as u128;
+        let input_with_fee = (input * fee_denominator).div_ceil(fee_denominator.checked_sub(self.trade_fee_numerator as u128)?);
+
+        input_with_fee.try_into().ok()
+    }
+
+    fn spot_price(&self) -> f64 {
+        self.balance_y as f64 / self.balance_x as f64
+    }
+
+    fn front_run_estimate(&self, is_swap_x_to_y: bool, input_amount: u64, min_output: u64) -> Option<f64> {
+        let (balance_in, balance_out) = oriented(self, is_swap_x_to_y);
+        let gamma = 1.0 - self.trade_fee_numerator as f64 / self.trade_fee_denominator as f64;
+
+        constant_product_front_run(balance_in, balance_out, gamma, input_amount, min_output)
+    }
+
+    fn with_balances(&self, balance_x: u64, balance_y: u64) -> Option<Box<dyn Pool>> {
+        Some(Box::new(RaydiumV4 { balance_x, balance_y, ..*self }))
+    }
+
+    fn clone_box(&self) -> Box<dyn Pool> {
+        Box::new(*self)
+    }
+}
@@ -0,0 +1,70 @@
+//! The largest front-run a victim's slippage tolerance lets through, i.e. the optimal
+//! sandwich size: past it the victim's swap would receive less than its minimum output.
+//! Constant product pools start from their closed form, every other pool from a
+//! galloping search, both end in an exact search against the pool's integer quotes.
+
+use super::pool::{after_swap, Pool};
+
+/// Victim output once a front-run of `front_run` went through in the victim's direction.
+pub fn victim_output_after(pool: &dyn Pool, is_swap_x_to_y: bool, input_amount: u64, front_run: u64) -> Option<u64> {
+    after_swap(pool, is_swap_x_to_y, front_run)?.quote_exact_in(is_swap_x_to_y, input_amount)
+}
+
+/// Largest front-run that keeps the victim's output at `min_output` or above, `None` if
+/// the victim's output is already below it. The output falls as the front-run grows, a
+/// front-run the pool can't fill counts as too large.
+pub fn max_front_run(pool: &dyn Pool, is_swap_x_to_y: bool, input_amount: u64, min_output: u64) -> Option<u64> {
+    let keeps = |front_run: u64| victim_output_after(pool, is_swap_x_to_y, input_amount, front_run).is_some_and(|output| output >= min_output);
+
+    if !keeps(0) {
+        return None;
+    }
+
+    let estimate = pool
+        .front_run_estimate(is_swap_x_to_y, input_amount, min_output)
+        .map_or(0, |estimate| estimate.clamp(0.0, u64::MAX as f64) as u64);
+
+    // bracket the answer in [kept, not_kept) by galloping away from the estimate
+    let (mut kept, mut not_kept) = if keeps(estimate) {
+        let mut step = 1u64;
+        let mut kept = estimate;
+
+        loop {
+            let next = kept.saturating_add(step);
+
+            if next == kept {
+                return Some(kept);
+            }
+            if !keeps(next) {
+                break (kept, next);
+            }
+            kept = next;
+            step = step.saturating_mul(2);
+        }
+    } else {
+        let mut step = 1u64;
+        let mut not_kept = estimate;
+
+        loop {
+            let next = not_kept.saturating_sub(step);
+
+            if keeps(next) {
+                break (next, not_kept);
+            }
+            not_kept = next;
+            step = step.saturating_mul(2);
+        }
+    };
+
+    while not_kept - kept > 1 {
+        let middle = kept + (not_kept - kept) / 2;
+
+        if keeps(middle) {
+            kept = middle;
+        } else {
+            not_kept = middle;
+        }
+    }
+
+    Some(kept)
+}
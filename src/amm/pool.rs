@@ -0,0 +1,125 @@
+//! Pool models behind one [`Pool`] trait, so sweeps and searches can run against the
+//! curve of the venue being protected instead of the fee-less `x * y = k` of [`swap`](super::swap).
+
+use std::fmt::Debug;
+
+use super::clmm::{Clmm, DEFAULT_LADDER};
+use super::constant_product::{ConstantProduct, RaydiumV4};
+use super::stable_swap::StableSwap;
+
+/// Basis points denominator, 10,000 = 100%.
+pub const BPS: u64 = 10_000;
+
+/// A two token pool with its current balances. Quotes never change the pool, `None` means
+/// the pool can't fill the trade (empty side, overflow, more output than the reserve).
+pub trait Pool: Debug + Send + Sync {
+    /// Short identifier, e.g. for reports and [`pool_by_name`].
+    fn name(&self) -> &'static str;
+
+    /// `(balance_x, balance_y)`.
+    fn balances(&self) -> (u64, u64);
+
+    /// Output received for `input_amount` in.
+    fn quote_exact_in(&self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64>;
+
+    /// Input needed to receive at least `output_amount`. Defaults to a binary search for
+    /// the smallest such input, exact for any quote that is monotone in the input.
+    fn quote_exact_out(&self, is_swap_x_to_y: bool, output_amount: u64) -> Option<u64> {
+        let reaches = |input_amount: u64| self.quote_exact_in(is_swap_x_to_y, input_amount).is_some_and(|output| output >= output_amount);
+
+        if output_amount == 0 {
+            return Some(0);
+        }
+
+        let mut high = 1u64;
+        while !reaches(high) {
+            if high == u64::MAX {
+                return None;
+            }
+            high = high.saturating_mul(2);
+        }
+
+        let mut low = high / 2;
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+
+            if reaches(middle) {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+
+        Some(if reaches(low) { low } else { high })
+    }
+
+    /// Swaps `input_amount` in and returns the output, `None` leaves the pool as is. The
+    /// whole input, fee included, stays in the pool.
+    fn apply_swap(&mut self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64>;
+
+    /// Marginal price of x in y (y received per x for an infinitesimal trade, fee excluded).
+    fn spot_price(&self) -> f64;
+
+    /// Closed form estimate of the largest front-run in the victim's direction that keeps
+    /// its output at `min_output` or above, for pools that have one. Only a starting point,
+    /// [`max_front_run`](super::front_run::max_front_run) settles it against the exact quotes.
+    fn front_run_estimate(&self, _is_swap_x_to_y: bool, _input_amount: u64, _min_output: u64) -> Option<f64> {
+        None
+    }
+
+    /// Current tick, for tick based pools.
+    fn tick(&self) -> Option<i32> {
+        None
+    }
+
+    /// The same pool with other balances, for pools whose state is their balances (a
+    /// donation followed by a sync, or a liquidity change). `None` for any other pool.
+    fn with_balances(&self, _balance_x: u64, _balance_y: u64) -> Option<Box<dyn Pool>> {
+        None
+    }
+
+    fn clone_box(&self) -> Box<dyn Pool>;
+}
+
+/// Balances once `input_amount` went in and `output` out, for pools whose state is their balances.
+pub(crate) fn swapped_balances((balance_x, balance_y): (u64, u64), is_swap_x_to_y: bool, input_amount: u64, output: u64) -> Option<(u64, u64)> {
+    if is_swap_x_to_y {
+        Some((balance_x.checked_add(input_amount)?, balance_y.checked_sub(output)?))
+    } else {
+        Some((balance_x.checked_sub(output)?, balance_y.checked_add(input_amount)?))
+    }
+}
+
+impl Clone for Box<dyn Pool> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Pool state after a swap, `self` is left as is.
+pub fn after_swap(pool: &dyn Pool, is_swap_x_to_y: bool, input_amount: u64) -> Option<Box<dyn Pool>> {
+    let mut pool = pool.clone_box();
+    pool.apply_swap(is_swap_x_to_y, input_amount)?;
+
+    Some(pool)
+}
+
+/// Looks up a pool model by name with default parameters:
+///
+/// - `constant_product` - no fee, what every experiment used so far
+/// - `uniswap_v2` - constant product with a 30 bps fee
+/// - `raydium_v4` - Raydium AMM v4 rounding with its 25/10,000 trade fee
+/// - `stable_swap` - StableSwap with A = 100 and a 4 bps fee
+/// - `clmm` - concentrated liquidity with a 30 bps fee, see [`Clmm::ladder`]
+pub fn pool_by_name(name: &str, balance_x: u64, balance_y: u64) -> Option<Box<dyn Pool>> {
+    let pool: Box<dyn Pool> = match name {
+        "constant_product" => Box::new(ConstantProduct::new(balance_x, balance_y)),
+        "uniswap_v2" => Box::new(ConstantProduct { fee_bps: 30, ..ConstantProduct::new(balance_x, balance_y) }),
+        "raydium_v4" => Box::new(RaydiumV4::new(balance_x, balance_y)),
+        "stable_swap" => Box::new(StableSwap::new(balance_x, balance_y, 100, 4)?),
+        "clmm" => Box::new(Clmm::ladder(balance_x, balance_y, 30, 60, &DEFAULT_LADDER)?),
+        _ => return None,
+    };
+
+    Some(pool)
+}
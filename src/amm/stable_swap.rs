@@ -0,0 +1,150 @@
+//! Curve StableSwap for two coins of the same precision:
+//! `A n^n (x + y) + D = A D n^n + D^(n+1) / (n^n x y)` with `n = 2`, solved with Curve's
+//! Newton iterations (`get_D`, `get_y`) in arbitrary precision, `D^3` exceeds u128.
+
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use super::constant_product::oriented;
+use super::pool::{swapped_balances, Pool, BPS};
+
+const COINS: u64 = 2;
+/// Curve gives up after this many iterations, so does this model.
+const MAX_ITERATIONS: usize = 255;
+
+/// StableSwap with the fee (bps) taken from the output like Curve. One unit is
+/// subtracted from every output to round against the trader, as `get_dy` does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StableSwap {
+    pub balance_x: u64,
+    pub balance_y: u64,
+    /// `A`, the curve is flatter around the 1:1 price the larger it is. Private so it
+    /// can't be 0, the Newton iterations divide by it.
+    amplification: u64,
+    /// 10,000 = 100%
+    pub fee_bps: u64,
+}
+
+impl StableSwap {
+    /// `None` for an amplification of 0.
+    pub fn new(balance_x: u64, balance_y: u64, amplification: u64, fee_bps: u64) -> Option<Self> {
+        (amplification > 0).then_some(StableSwap { balance_x, balance_y, amplification, fee_bps })
+    }
+
+    pub fn amplification(&self) -> u64 {
+        self.amplification
+    }
+
+    // A n^n
+    fn ann(&self) -> BigUint {
+        BigUint::from(self.amplification) * COINS.pow(COINS as u32)
+    }
+
+    /// The invariant `D` for the current balances, Curve's `get_D`.
+    pub fn invariant(&self) -> BigUint {
+        let (x, y) = (BigUint::from(self.balance_x), BigUint::from(self.balance_y));
+        let sum = &x + &y;
+
+        if sum.is_zero() || x.is_zero() || y.is_zero() {
+            return sum;
+        }
+
+        let ann = self.ann();
+        let coins = BigUint::from(COINS);
+        let mut d = sum.clone();
+
+        for _ in 0..MAX_ITERATIONS {
+            let d_p = &d * &d / (&x * &coins) * &d / (&y * &coins);
+            let previous = d.clone();
+
+            d = (&ann * &sum + &d_p * &coins) * &d / ((&ann - 1u32) * &d + (&coins + 1u32) * &d_p);
+
+            if abs_diff(&d, &previous) <= BigUint::from(1u32) {
+                break;
+            }
+        }
+
+        d
+    }
+
+    /// Balance of the other coin once one coin's balance is `balance`, Curve's `get_y`.
+    fn other_balance(&self, balance: &BigUint, d: &BigUint) -> Option<BigUint> {
+        if balance.is_zero() {
+            return None;
+        }
+
+        let ann = self.ann();
+        let coins = BigUint::from(COINS);
+
+        let c = d * d / (balance * &coins) * d / (&ann * &coins);
+        let b = balance + d / &ann;
+        let mut y = d.clone();
+
+        for _ in 0..MAX_ITERATIONS {
+            let previous = y.clone();
+            // 2y + b > D along the iteration
+            y = (&y * &y + &c) / (&y * 2u32 + &b - d);
+
+            if abs_diff(&y, &previous) <= BigUint::from(1u32) {
+                break;
+            }
+        }
+
+        Some(y)
+    }
+}
+
+fn abs_diff(a: &BigUint, b: &BigUint) -> BigUint {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+impl Pool for StableSwap {
+    fn name(&self) -> &'static str {
+        "stable_swap"
+    }
+
+    fn balances(&self) -> (u64, u64) {
+        (self.balance_x, self.balance_y)
+    }
+
+    fn apply_swap(&mut self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let output = self.quote_exact_in(is_swap_x_to_y, input_amount)?;
+        (self.balance_x, self.balance_y) = swapped_balances(self.balances(), is_swap_x_to_y, input_amount, output)?;
+
+        Some(output)
+    }
+
+    fn quote_exact_in(&self, is_swap_x_to_y: bool, input_amount: u64) -> Option<u64> {
+        let (balance_in, balance_out) = oriented(self, is_swap_x_to_y);
+
+        if balance_in == 0 || balance_out == 0 {
+            return None;
+        }
+
+        let new_balance_out = self.other_balance(&BigUint::from(balance_in + input_amount as u128), &self.invariant())?;
+        let output = balance_out.saturating_sub(new_balance_out.to_u128()?.saturating_add(1)) as u64;
+
+        Some(output - (output as u128 * self.fee_bps.min(BPS) as u128 / BPS as u128) as u64)
+    }
+
+    /// `-dy/dx` from the partial derivatives of the invariant.
+    fn spot_price(&self) -> f64 {
+        let (x, y) = (self.balance_x as f64, self.balance_y as f64);
+        let ann = self.amplification as f64 * COINS.pow(COINS as u32) as f64;
+        let d_cubed = self.invariant().to_f64().unwrap_or(f64::INFINITY).powi(3) / 4.0;
+
+        (ann + d_cubed / (x * x * y)) / (ann + d_cubed / (x * y * y))
+    }
+
+    fn with_balances(&self, balance_x: u64, balance_y: u64) -> Option<Box<dyn Pool>> {
+        Some(Box::new(StableSwap { balance_x, balance_y, ..*self }))
+    }
+
+    fn clone_box(&self) -> Box<dyn Pool> {
+        Box::new(*self)
+    }
+}
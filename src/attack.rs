@@ -0,0 +1,229 @@
+//! Adversarial search for the most profitable sandwich a victim's commitment does not
+//! reject, the worst case leakage of a scheme.
+//!
+//! The attacker front-runs in either direction, lets the victim swap and back-runs in
+//! the opposite direction with any part of what the front-run bought. Whatever it keeps
+//! is valued at the pool's spot price before the attack, so the profit is in the
+//! front-run's input token. Only the victim's output is checked against its commitment,
+//! with [`VictimCommitment::rejects`].
+//!
+//! Acceptance is not monotone in the front-run size, so for each direction the search
+//! samples front-runs on a geometric grid up to the pool's balance of the front-run's
+//! input token, binary searches every accepted-to-rejected step of the grid for its last
+//! accepted front-run and then picks the best back-run for each accepted front-run.
+
+use std::fmt;
+
+use crate::amm::{after_swap, max_front_run, Pool};
+use crate::hash::LshHash;
+use crate::sandwich::{VictimCommitment, VictimTrade};
+
+/// One accepted attack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attack {
+    /// The front-run swaps in the victim's direction.
+    pub with_victim: bool,
+    pub front_run_input: u64,
+    pub front_run_output: u64,
+    pub victim_output: u64,
+    /// Part of the front-run output sold back, at most `front_run_output`.
+    pub back_run_input: u64,
+    pub back_run_output: u64,
+    /// In the front-run's input token, see the module docs.
+    pub attacker_pnl: f64,
+    /// Output the victim would have received without the attack minus what it received.
+    pub victim_loss: i128,
+    pub distance_to_upper: u32,
+    pub distance_to_lower: u32,
+}
+
+impl Attack {
+    /// How far below its lower slippage boundary the victim's output went undetected.
+    pub fn loss_beyond_slippage(&self, commitment: &VictimCommitment) -> u64 {
+        commitment.lower_output.saturating_sub(self.victim_output)
+    }
+}
+
+/// Result of [`search_attacks`].
+#[derive(Clone, Debug)]
+pub struct AttackReport {
+    pub pool: &'static str,
+    pub commitment: VictimCommitment,
+    /// Best accepted attack front-running in the victim's direction.
+    pub with_victim: Option<Attack>,
+    /// Best accepted attack front-running against it.
+    pub against_victim: Option<Attack>,
+    /// Front-run sizes checked against the commitment.
+    pub evaluations: usize,
+}
+
+impl AttackReport {
+    /// The more profitable of the two directions.
+    pub fn best(&self) -> Option<&Attack> {
+        self.with_victim.iter().chain(&self.against_victim).max_by(|a, b| a.attacker_pnl.total_cmp(&b.attacker_pnl))
+    }
+}
+
+impl fmt::Display for AttackReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: output {} | upper {} | lower {} | boundary distance {} | evaluations {}",
+            self.pool,
+            self.commitment.output,
+            self.commitment.upper_output,
+            self.commitment.lower_output,
+            self.commitment.boundary_distance(),
+            self.evaluations
+        )?;
+
+        for (direction, attack) in [("with victim", &self.with_victim), ("against victim", &self.against_victim)] {
+            match attack {
+                Some(attack) => writeln!(
+                    f,
+                    "{}: front-run {} | back-run {} of {} | attacker pnl {:.0} | victim output {} | victim loss {} | beyond slippage {} | to upper {} | to lower {}",
+                    direction,
+                    attack.front_run_input,
+                    attack.back_run_input,
+                    attack.front_run_output,
+                    attack.attacker_pnl,
+                    attack.victim_output,
+                    attack.victim_loss,
+                    attack.loss_beyond_slippage(&self.commitment),
+                    attack.distance_to_upper,
+                    attack.distance_to_lower
+                )?,
+                None => writeln!(f, "{}: none accepted", direction)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Searches both front-run directions with `samples` grid points each. `None` if the pool
+/// can't fill the victim's trade.
+pub fn search_attacks<F: Fn(u64) -> LshHash>(pool: &dyn Pool, victim: &VictimTrade, samples: usize, lsh: F) -> Option<AttackReport> {
+    let commitment = VictimCommitment::new(pool, victim, &lsh)?;
+
+    let mut evaluations = 0;
+    let with_victim = search_direction(pool, victim, &commitment, true, samples, &lsh, &mut evaluations);
+    let against_victim = search_direction(pool, victim, &commitment, false, samples, &lsh, &mut evaluations);
+
+    Some(AttackReport { pool: pool.name(), commitment, with_victim, against_victim, evaluations })
+}
+
+fn search_direction<F: Fn(u64) -> LshHash>(
+    pool: &dyn Pool,
+    victim: &VictimTrade,
+    commitment: &VictimCommitment,
+    with_victim: bool,
+    samples: usize,
+    lsh: F,
+    evaluations: &mut usize,
+) -> Option<Attack> {
+    let front_run_x_to_y = victim.is_swap_x_to_y == with_victim;
+    let (balance_x, balance_y) = pool.balances();
+    let max_input = if front_run_x_to_y { balance_x } else { balance_y }.max(1);
+
+    let mut accepts = |front_run: u64| {
+        *evaluations += 1;
+
+        victim_output_after(pool, victim, front_run_x_to_y, front_run).is_some_and(|output| output > 0 && !commitment.rejects(&lsh(output)))
+    };
+
+    let mut grid: Vec<u64> = (1..=samples).map(|k| (max_input as f64).powf(k as f64 / samples as f64).round() as u64).collect();
+    if with_victim {
+        // the grid rarely hits the slippage boundary exactly
+        grid.extend(max_front_run(pool, victim.is_swap_x_to_y, victim.input_amount, commitment.lower_output));
+    }
+    grid.sort_unstable();
+    grid.dedup();
+
+    let accepted: Vec<bool> = grid.iter().map(|&front_run| accepts(front_run)).collect();
+    let mut candidates = Vec::new();
+
+    for (i, &front_run) in grid.iter().enumerate() {
+        if !accepted[i] {
+            continue;
+        }
+        candidates.push(front_run);
+
+        let Some(&rejected) = grid.get(i + 1).filter(|_| !accepted[i + 1]) else { continue };
+        let (mut kept, mut not_kept) = (front_run, rejected);
+
+        while not_kept - kept > 1 {
+            let middle = kept + (not_kept - kept) / 2;
+
+            if accepts(middle) {
+                kept = middle;
+            } else {
+                not_kept = middle;
+            }
+        }
+        candidates.push(kept);
+    }
+
+    // value of one unit of the front-run's output in its input token
+    let price = if front_run_x_to_y { 1.0 / pool.spot_price() } else { pool.spot_price() };
+
+    candidates
+        .into_iter()
+        .filter_map(|front_run| best_back_run(pool, victim, commitment, front_run_x_to_y, with_victim, front_run, price, &lsh))
+        .max_by(|a, b| a.attacker_pnl.total_cmp(&b.attacker_pnl))
+}
+
+fn victim_output_after(pool: &dyn Pool, victim: &VictimTrade, front_run_x_to_y: bool, front_run: u64) -> Option<u64> {
+    after_swap(pool, front_run_x_to_y, front_run)?.quote_exact_in(victim.is_swap_x_to_y, victim.input_amount)
+}
+
+/// The attack with `front_run` and the back-run that maximizes the attacker's profit. The
+/// profit is concave in the back-run for the pool models here, so a ternary search finds it.
+#[allow(clippy::too_many_arguments)]
+fn best_back_run<F: Fn(u64) -> LshHash>(
+    pool: &dyn Pool,
+    victim: &VictimTrade,
+    commitment: &VictimCommitment,
+    front_run_x_to_y: bool,
+    with_victim: bool,
+    front_run: u64,
+    price: f64,
+    lsh: F,
+) -> Option<Attack> {
+    let mut pool = pool.clone_box();
+
+    let front_run_output = pool.apply_swap(front_run_x_to_y, front_run)?;
+    let victim_output = pool.apply_swap(victim.is_swap_x_to_y, victim.input_amount)?;
+
+    let back_run_output = |back_run: u64| if back_run == 0 { Some(0) } else { pool.quote_exact_in(!front_run_x_to_y, back_run) };
+    let pnl = |back_run: u64| {
+        back_run_output(back_run).map_or(f64::NEG_INFINITY, |output| output as f64 + (front_run_output - back_run) as f64 * price - front_run as f64)
+    };
+
+    let (mut low, mut high) = (0, front_run_output);
+    while high - low > 2 {
+        let third = (high - low) / 3;
+
+        if pnl(low + third) < pnl(high - third) {
+            low += third;
+        } else {
+            high -= third;
+        }
+    }
+    let back_run_input = (low..=high).max_by(|&a, &b| pnl(a).total_cmp(&pnl(b)))?;
+
+    let hash = lsh(victim_output);
+
+    Some(Attack {
+        with_victim,
+        front_run_input: front_run,
+        front_run_output,
+        victim_output,
+        back_run_input,
+        back_run_output: back_run_output(back_run_input)?,
+        attacker_pnl: pnl(back_run_input),
+        victim_loss: commitment.output as i128 - victim_output as i128,
+        distance_to_upper: commitment.upper_hash.hamming_distance(&hash),
+        distance_to_lower: commitment.lower_hash.hamming_distance(&hash),
+    })
+}
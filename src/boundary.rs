@@ -0,0 +1,53 @@
+use crate::amm::{slippage_bounds, ConstantProduct, Pool};
+use crate::hash::LshHash;
+
+/// Swaps against the constant product pool and hashes the received amount with `lsh`.
+/// Returns the hash and the received amount.
+pub fn get_hash<F: Fn(u64) -> LshHash>(
+    is_swap_x_to_y: bool,
+    balance_x: u64,
+    balance_y: u64,
+    input_amount: u64,
+    lsh: F,
+) -> (LshHash, u64) {
+    get_pool_hash(&ConstantProduct::new(balance_x, balance_y), is_swap_x_to_y, input_amount, lsh).expect("the pool cannot fill the trade")
+}
+
+/// Same as [`get_hash`] but hashes the two boundaries around the received amount
+/// (+/- slippage, 10,000 = 100%). Returns `([upper_hash, lower_hash], upper_output,
+/// lower_output, output)`.
+pub fn get_boundary_hashes<F: Fn(u64) -> LshHash>(
+    is_swap_x_to_y: bool,
+    balance_x: u64,
+    balance_y: u64,
+    input_amount: u64,
+    slippage: u64,
+    lsh: F,
+) -> ([LshHash; 2], u64, u64, u64) {
+    get_pool_boundary_hashes(&ConstantProduct::new(balance_x, balance_y), is_swap_x_to_y, input_amount, slippage, lsh)
+        .expect("the pool cannot fill the trade")
+}
+
+/// [`get_hash`] against any pool model, `None` if the pool can't fill the trade.
+pub fn get_pool_hash<F: Fn(u64) -> LshHash>(pool: &dyn Pool, is_swap_x_to_y: bool, input_amount: u64, lsh: F) -> Option<(LshHash, u64)> {
+    let output = pool.quote_exact_in(is_swap_x_to_y, input_amount)?;
+
+    Some((lsh(output), output))
+}
+
+/// [`get_boundary_hashes`] against any pool model, `None` if the pool can't fill the trade.
+pub fn get_pool_boundary_hashes<F: Fn(u64) -> LshHash>(
+    pool: &dyn Pool,
+    is_swap_x_to_y: bool,
+    input_amount: u64,
+    slippage: u64,
+    lsh: F,
+) -> Option<([LshHash; 2], u64, u64, u64)> {
+    let output = pool.quote_exact_in(is_swap_x_to_y, input_amount)?;
+    let (upper_output, lower_output) = slippage_bounds(output, slippage);
+
+    let upper_lsh_hash = lsh(upper_output);
+    let lower_lsh_hash = lsh(lower_output);
+
+    Some(([upper_lsh_hash, lower_lsh_hash], upper_output, lower_output, output))
+}
@@ -0,0 +1,18 @@
+//! Glue between the Rust hashes and the circom circuit.
+//!
+//! The circuit takes the trade scenario `get_boundary_hashes` works on as its input
+//! signals (`salt`, `is_swap_x_to_y`, `balance_x`, `balance_y`, `input_amount`,
+//! `slippage`) and outputs `base_hash`, `upper_hash` and `lower_hash`, one signal per
+//! projection, bit `i` being bit `i` of the [`LshHash`](crate::LshHash).
+
+pub mod check;
+pub mod codegen;
+pub mod estimate;
+pub mod input;
+pub mod r1cs;
+pub mod witness;
+
+pub use check::{check_witness, Backend, WitnessReport};
+pub use codegen::{CircuitConfig, CodegenError, SaltHandling};
+pub use input::{Scenario, ScenarioHashes};
+pub use witness::{CircomFileError, SymbolMap, Witness};
@@ -0,0 +1,188 @@
+//! Bit-for-bit comparison of the hashes in a circuit witness with the Rust hashes for the
+//! same scenario, the guard against the Rust code and the circuit drifting apart.
+
+use num_bigint::BigInt;
+use std::fmt;
+
+use super::input::Scenario;
+use super::witness::{CircomFileError, SymbolMap, Witness};
+use crate::field::{field_partial_sums, to_signed, Fr};
+use crate::hash::LshHash;
+use crate::matrix::ProjectionMatrix;
+
+/// Output signal prefixes, the circuit outputs `main.<prefix>_hash[i]` and, if it keeps
+/// them, the projection sums as `main.<prefix>_sum[i]`.
+pub const OUTPUTS: [&str; 3] = ["base", "upper", "lower"];
+
+/// Rust implementation the witness is compared with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// `generate_lsh_rust`
+    Rust,
+    /// `generate_lsh_circuit`
+    Circuit,
+    /// `generate_lsh_field`, what the circuit is supposed to compute.
+    #[default]
+    Field,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Rust, Backend::Circuit, Backend::Field];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Rust => "rust",
+            Backend::Circuit => "circuit",
+            Backend::Field => "field",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL.into_iter().find(|backend| backend.name() == name)
+    }
+
+    pub fn hash(&self, matrix: &ProjectionMatrix, features: &[u64]) -> LshHash {
+        match self {
+            Backend::Rust => matrix.hash_rust(features),
+            Backend::Circuit => matrix.hash_circuit(features),
+            Backend::Field => matrix.hash_field(features),
+        }
+    }
+}
+
+/// One hash bit the witness and Rust disagree on.
+#[derive(Clone, Debug)]
+pub struct BitMismatch {
+    pub projection: usize,
+    pub witness_bit: bool,
+    pub rust_bit: bool,
+    /// Signed running sums of the projection, entry `k` over the first `k + 1` features,
+    /// computed in the field like the circuit.
+    pub partial_sums: Vec<BigInt>,
+    /// The circuit's own sum, if the witness has the `_sum` signal.
+    pub witness_sum: Option<BigInt>,
+}
+
+/// One output hash of the circuit next to its Rust counterpart.
+#[derive(Clone, Debug)]
+pub struct HashComparison {
+    pub output: &'static str,
+    /// Received amount the hash is over.
+    pub amount: u64,
+    pub features: Vec<u64>,
+    pub witness_hash: LshHash,
+    pub rust_hash: LshHash,
+    pub mismatches: Vec<BitMismatch>,
+}
+
+/// Result of [`check_witness`].
+#[derive(Clone, Debug)]
+pub struct WitnessReport {
+    pub backend: Backend,
+    pub hashes: Vec<HashComparison>,
+    /// The circuit's `salt_commitment` output, `None` if it has none (a baked salt).
+    pub witness_salt_commitment: Option<Fr>,
+    /// [`Salt::commitment`](crate::salt::Salt::commitment) of the witness's salt and blinding.
+    pub rust_salt_commitment: Fr,
+}
+
+impl WitnessReport {
+    pub fn is_match(&self) -> bool {
+        self.hashes.iter().all(|hash| hash.mismatches.is_empty())
+            && self.witness_salt_commitment.is_none_or(|commitment| commitment == self.rust_salt_commitment)
+    }
+}
+
+impl fmt::Display for WitnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(commitment) = self.witness_salt_commitment {
+            if commitment == self.rust_salt_commitment {
+                writeln!(f, "salt commitment matches")?;
+            } else {
+                writeln!(f, "salt commitment: witness {} rust {}", commitment, self.rust_salt_commitment)?;
+            }
+        }
+
+        for hash in &self.hashes {
+            writeln!(
+                f,
+                "{} ({}): {} of {} bits differ from {}",
+                hash.output,
+                hash.amount,
+                hash.mismatches.len(),
+                hash.witness_hash.len(),
+                self.backend.name()
+            )?;
+
+            if hash.mismatches.is_empty() {
+                continue;
+            }
+
+            writeln!(f, "  witness {}", hash.witness_hash)?;
+            writeln!(f, "  rust    {}", hash.rust_hash)?;
+            writeln!(f, "  features {:?}", hash.features)?;
+
+            for mismatch in &hash.mismatches {
+                write!(
+                    f,
+                    "  projection {}: witness {} rust {}, sum {}",
+                    mismatch.projection,
+                    mismatch.witness_bit as u8,
+                    mismatch.rust_bit as u8,
+                    mismatch.partial_sums.last().map_or_else(|| "0".to_string(), BigInt::to_string)
+                )?;
+
+                if let Some(witness_sum) = &mismatch.witness_sum {
+                    write!(f, " (witness sum {})", witness_sum)?;
+                }
+
+                writeln!(f)?;
+                writeln!(f, "    partial sums {:?}", mismatch.partial_sums.iter().map(BigInt::to_string).collect::<Vec<_>>())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the base, upper and lower hash out of `witness` and diffs them with `backend`
+/// over the same amounts, and the salt commitment with the scenario's. Missing hash
+/// signals are an error, missing sum or salt commitment signals are not.
+pub fn check_witness(scenario: &Scenario, witness: &Witness, symbols: &SymbolMap, backend: Backend) -> Result<WitnessReport, CircomFileError> {
+    let evaluated = scenario.evaluate();
+    let amounts = [evaluated.output, evaluated.upper_output, evaluated.lower_output];
+
+    let matrix = scenario.matrix();
+    let mut hashes = Vec::with_capacity(OUTPUTS.len());
+
+    for (output, amount) in OUTPUTS.into_iter().zip(amounts) {
+        let features = scenario.features(amount);
+
+        let rust_hash = backend.hash(&matrix, &features);
+        let witness_hash = (0..rust_hash.len())
+            .map(|i| witness.signal_bit(symbols, &format!("main.{}_hash[{}]", output, i)))
+            .collect::<Result<LshHash, _>>()?;
+
+        let mismatches = (&witness_hash ^ &rust_hash)
+            .iter()
+            .enumerate()
+            .filter(|&(_, differs)| differs)
+            .map(|(projection, _)| BitMismatch {
+                projection,
+                witness_bit: witness_hash.get(projection),
+                rust_bit: rust_hash.get(projection),
+                partial_sums: field_partial_sums(&features, matrix.row(projection as u64)).iter().map(to_signed).collect(),
+                witness_sum: witness.signal(symbols, &format!("main.{}_sum[{}]", output, projection)).ok().map(|sum| to_signed(&sum)),
+            })
+            .collect();
+
+        hashes.push(HashComparison { output, amount, features, witness_hash, rust_hash, mismatches });
+    }
+
+    Ok(WitnessReport {
+        backend,
+        hashes,
+        witness_salt_commitment: witness.signal(symbols, "main.salt_commitment").ok(),
+        rust_salt_commitment: scenario.config.salt.commitment(),
+    })
+}
@@ -0,0 +1,741 @@
+//! Generates the circom template for an LSH configuration, so the circuit is derived
+//! from the same encoder and projection code as the Rust hashes instead of rewritten by
+//! hand after every change.
+//!
+//! The configuration is JSON:
+//!
+//! ```json
+//! {
+//!     "name": "LshBoundary",
+//!     "encoder": "weighted_nibbles",
+//!     "feature_count": 16,
+//!     "projection_count": 128,
+//!     "salt": "private",
+//!     "coefficient_bits": 64,
+//!     "decoding": "drop_msb",
+//!     "input_index_offset": 0,
+//!     "include_path": "circomlib/circuits",
+//!     "test_vector": { "balance_x": 1000000, "balance_y": 2000000, "input_amount": 5000, "slippage": 50, "is_swap_x_to_y": true, "salt": "test", "salt_blinding": 0 }
+//! }
+//! ```
+//!
+//! Only `encoder` and `projection_count` are required. `feature_count` is checked against
+//! the encoder. `salt` is `"private"`, `"public"`, a non zero number or `"test"`, a number
+//! bakes the projections for that salt into the template as constants (no Poseidon in the
+//! circuit, the `salt` input is constrained to it) and `"test"` bakes salt 0, see
+//! [`Salt::test`]. The test vector's `salt` takes the same non zero number or `"test"`.
+//!
+//! A private or public salt is bound by the `salt_commitment` output, Poseidon(salt,
+//! salt_blinding) like [`Salt::commitment`], which comes first in `public.json`. A
+//! verifier compares it with the commitment of the order, otherwise a prover could hash
+//! with any salt. `salt_blinding` is a private input, a number in the test vector. `coefficient_bits` keeps the low bits of each
+//! projection and decodes them at that width, 64 is the coefficient Rust uses.
+//!
+//! The encoder has to be linear in the bits of the amount (every integer encoder except
+//! `averaged_weighted_nibbles`), its weights are read off the Rust encoder and checked.
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde_json::Value;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::input::Scenario;
+use crate::encoder::{encoder_by_name, FeatureEncoder, Features};
+use crate::field::Fr;
+use crate::lsh::LshConfig;
+use crate::matrix::ProjectionMatrix;
+use crate::projection::{Decoding, Poseidon};
+use crate::salt::{Salt, SaltError};
+
+/// Bits of the amounts the encoder sees.
+const AMOUNT_BITS: usize = 64;
+
+#[derive(Debug)]
+pub enum CodegenError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Missing or mistyped configuration field.
+    BadField(&'static str),
+    UnknownEncoder(String),
+    UnknownDecoding(String),
+    FeatureCountMismatch { declared: usize, actual: usize },
+    /// Float features or features that are not a weighted sum of the amount bits.
+    NonLinearEncoder(&'static str),
+    CoefficientBits(u32),
+    Salt(SaltError),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Io(err) => write!(f, "io error: {}", err),
+            CodegenError::Json(err) => write!(f, "invalid json: {}", err),
+            CodegenError::BadField(field) => write!(f, "missing or invalid field '{}'", field),
+            CodegenError::UnknownEncoder(name) => write!(f, "unknown encoder '{}'", name),
+            CodegenError::UnknownDecoding(name) => write!(f, "unknown decoding '{}'", name),
+            CodegenError::FeatureCountMismatch { declared, actual } => {
+                write!(f, "feature_count is {} but the encoder produces {}", declared, actual)
+            }
+            CodegenError::NonLinearEncoder(name) => write!(f, "encoder '{}' is not linear in the amount bits", name),
+            CodegenError::CoefficientBits(bits) => write!(f, "coefficient_bits must be between 2 and 64, got {}", bits),
+            CodegenError::Salt(err) => write!(f, "invalid salt: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+impl From<io::Error> for CodegenError {
+    fn from(err: io::Error) -> Self {
+        CodegenError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for CodegenError {
+    fn from(err: serde_json::Error) -> Self {
+        CodegenError::Json(err)
+    }
+}
+
+impl From<SaltError> for CodegenError {
+    fn from(err: SaltError) -> Self {
+        CodegenError::Salt(err)
+    }
+}
+
+/// Where the salt comes from in the generated circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaltHandling {
+    Private,
+    Public,
+    /// Projections precomputed for this salt.
+    Baked(Salt),
+}
+
+/// Everything the generated template depends on.
+#[derive(Debug)]
+pub struct CircuitConfig {
+    /// Name of the main template, helper templates are prefixed with it.
+    pub name: String,
+    pub encoder: Box<dyn FeatureEncoder>,
+    pub projection_count: u64,
+    pub salt: SaltHandling,
+    pub coefficient_bits: u32,
+    pub decoding: Decoding,
+    pub input_index_offset: u64,
+    /// Directory of the circomlib circuits in the `include` lines.
+    pub include_path: String,
+}
+
+impl CircuitConfig {
+    pub fn load(path: &Path) -> Result<Self, CodegenError> {
+        Self::from_json(&serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn from_json(json: &Value) -> Result<Self, CodegenError> {
+        let encoder_name = json["encoder"].as_str().ok_or(CodegenError::BadField("encoder"))?;
+        let encoder = encoder_by_name(encoder_name).ok_or_else(|| CodegenError::UnknownEncoder(encoder_name.to_string()))?;
+
+        if let Some(declared) = optional_u64(json, "feature_count")? {
+            if declared as usize != encoder.dimension() {
+                return Err(CodegenError::FeatureCountMismatch { declared: declared as usize, actual: encoder.dimension() });
+            }
+        }
+
+        let salt = match &json["salt"] {
+            Value::Null => SaltHandling::Private,
+            Value::String(handling) if handling == "private" => SaltHandling::Private,
+            Value::String(handling) if handling == "public" => SaltHandling::Public,
+            value => SaltHandling::Baked(salt_value(value)?),
+        };
+
+        let decoding = match json["decoding"].as_str() {
+            None => Decoding::CIRCUIT,
+            Some(name) => Decoding::from_name(name).ok_or_else(|| CodegenError::UnknownDecoding(name.to_string()))?,
+        };
+
+        let coefficient_bits = optional_u64(json, "coefficient_bits")?.unwrap_or(64) as u32;
+        if !(2..=64).contains(&coefficient_bits) {
+            return Err(CodegenError::CoefficientBits(coefficient_bits));
+        }
+
+        Ok(CircuitConfig {
+            name: json["name"].as_str().unwrap_or("LshBoundary").to_string(),
+            encoder,
+            projection_count: optional_u64(json, "projection_count")?.ok_or(CodegenError::BadField("projection_count"))?,
+            salt,
+            coefficient_bits,
+            decoding,
+            input_index_offset: optional_u64(json, "input_index_offset")?.unwrap_or(0),
+            include_path: json["include_path"].as_str().unwrap_or("circomlib/circuits").to_string(),
+        })
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.encoder.dimension()
+    }
+
+    /// Rust configuration hashing like the circuit does for `salt`.
+    pub fn lsh_config(&self, salt: Salt) -> LshConfig {
+        LshConfig { input_index_offset: self.input_index_offset, source: &Poseidon, decoding: self.decoding, ..LshConfig::new(self.projection_count, salt) }
+    }
+
+    /// Coefficients the circuit uses for `salt`, i.e. narrowed to `coefficient_bits`.
+    pub fn matrix(&self, salt: Salt) -> ProjectionMatrix {
+        let matrix = ProjectionMatrix::cached(&self.lsh_config(salt), self.feature_count() as u64);
+
+        matrix.map_coefficients(|coefficient| narrow_coefficient(coefficient, self.decoding, self.coefficient_bits))
+    }
+
+    /// `weights[i][k]`, the weight of amount bit `k` (from the LSB) in feature `i`. Read
+    /// off single bit amounts and checked on random ones.
+    pub fn encoder_weights(&self) -> Result<Vec<[u64; AMOUNT_BITS]>, CodegenError> {
+        let non_linear = || CodegenError::NonLinearEncoder(self.encoder.name());
+        let integer = |value: u64| match self.encoder.encode(value) {
+            Features::Integer(features) => Ok(features),
+            Features::Float(_) => Err(non_linear()),
+        };
+
+        let mut weights = vec![[0u64; AMOUNT_BITS]; self.feature_count()];
+        for (k, column) in (0..AMOUNT_BITS).map(|k| integer(1 << k)).enumerate() {
+            for (row, feature) in weights.iter_mut().zip(column?) {
+                row[k] = feature;
+            }
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let samples = (0..256).map(|_| rng.random::<u64>() >> rng.random_range(0..64)).chain([0, u64::MAX]);
+
+        for value in samples {
+            let linear = weights.iter().map(|row| (0..AMOUNT_BITS).map(|k| ((value >> k) & 1) as u128 * row[k] as u128).sum::<u128>());
+
+            if !integer(value)?.into_iter().map(u128::from).eq(linear) {
+                return Err(non_linear());
+            }
+        }
+
+        Ok(weights)
+    }
+
+    /// Rust side of the test vector, `input.json` and `expected.json` for `scenario` with
+    /// the hashes computed from [`CircuitConfig::matrix`].
+    pub fn test_vector(&self, scenario: &Scenario) -> (Value, Value) {
+        let mut input = scenario.input_json();
+        let mut expected = scenario.hashes_json(&scenario.evaluate_with(&self.matrix(scenario.config.salt)));
+
+        match self.salt {
+            SaltHandling::Baked(_) => {
+                input.as_object_mut().unwrap().remove("salt_blinding");
+                expected.as_object_mut().unwrap().remove("salt_commitment");
+                expected["public"].as_array_mut().unwrap().remove(0);
+            }
+            SaltHandling::Public => expected["public"].as_array_mut().unwrap().push(input["salt"].clone()),
+            SaltHandling::Private => {}
+        }
+
+        (input, expected)
+    }
+
+    /// Test vector scenario from the `test_vector` object of the configuration, any
+    /// missing value takes the default below.
+    pub fn scenario(&self, json: &Value) -> Result<Scenario, CodegenError> {
+        let vector = &json["test_vector"];
+        let value = |field: &'static str, default: u64| optional_u64(vector, field).map(|value| value.unwrap_or(default));
+
+        let salt = match self.salt {
+            SaltHandling::Baked(salt) => salt,
+            _ if vector["salt"].is_null() => Salt::test(),
+            _ => match salt_value(&vector["salt"])? {
+                salt if salt.is_test() => salt,
+                salt => Salt::new(salt.value(), Fr::from(value("salt_blinding", 0)?))?,
+            },
+        };
+        let is_swap_x_to_y = match &vector["is_swap_x_to_y"] {
+            Value::Null => true,
+            Value::Bool(is_swap_x_to_y) => *is_swap_x_to_y,
+            other => other.as_u64().ok_or(CodegenError::BadField("is_swap_x_to_y"))? != 0,
+        };
+
+        Ok(Scenario {
+            is_swap_x_to_y,
+            balance_x: value("balance_x", 1_000_000)?,
+            balance_y: value("balance_y", 2_000_000)?,
+            input_amount: value("input_amount", 5_000)?,
+            slippage: value("slippage", 50)?,
+            encoder: encoder_by_name(self.encoder.name()).unwrap(),
+            config: self.lsh_config(salt),
+            coefficient_bits: self.coefficient_bits,
+        })
+    }
+
+    /// The `.circom` source.
+    pub fn generate(&self) -> Result<String, CodegenError> {
+        let weights = self.encoder_weights()?;
+        let mut out = String::new();
+
+        self.write_header(&mut out);
+        self.write_swap(&mut out);
+        self.write_slippage(&mut out);
+        self.write_encoder(&mut out, &weights);
+        if !matches!(self.salt, SaltHandling::Baked(_)) {
+            self.write_coefficients(&mut out);
+        }
+        self.write_project(&mut out);
+        self.write_sign(&mut out);
+        self.write_main(&mut out);
+
+        Ok(out)
+    }
+
+    /// Writes `<name>.circom`, `input.json` and `expected.json` into `dir`.
+    pub fn export(&self, dir: &Path, scenario: &Scenario) -> Result<(), CodegenError> {
+        let source = self.generate()?;
+        let (input, expected) = self.test_vector(scenario);
+
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(format!("{}.circom", self.name)), source)?;
+        fs::write(dir.join("input.json"), serde_json::to_string_pretty(&input)? + "\n")?;
+        fs::write(dir.join("expected.json"), serde_json::to_string_pretty(&expected)? + "\n")?;
+
+        Ok(())
+    }
+
+    fn salt_description(&self) -> String {
+        match self.salt {
+            SaltHandling::Private => "private salt behind a salt commitment".to_string(),
+            SaltHandling::Public => "public salt behind a salt commitment".to_string(),
+            SaltHandling::Baked(salt) => format!("salt {} baked in", salt.value()),
+        }
+    }
+
+    fn write_header(&self, out: &mut String) {
+        let include = &self.include_path;
+
+        writeln!(out, "pragma circom 2.1.0;\n").unwrap();
+        if !matches!(self.salt, SaltHandling::Baked(_)) {
+            writeln!(out, "include \"{}/poseidon.circom\";", include).unwrap();
+        }
+        for file in ["bitify", "comparators", "sign"] {
+            writeln!(out, "include \"{}/{}.circom\";", include, file).unwrap();
+        }
+
+        writeln!(out, "\n// Generated by circom_codegen from the Rust LSH configuration, do not edit by hand.").unwrap();
+        writeln!(
+            out,
+            "// encoder {} ({} features), {} projections, {} bit coefficients decoded as {}, input index offset {}, {}.",
+            self.encoder.name(),
+            self.feature_count(),
+            self.projection_count,
+            self.coefficient_bits,
+            self.decoding.name(),
+            self.input_index_offset,
+            self.salt_description()
+        )
+        .unwrap();
+        writeln!(out, "// input.json / expected.json next to this file are the Rust computed test vector.\n").unwrap();
+    }
+
+    fn write_swap(&self, out: &mut String) {
+        writeln!(
+            out,
+            r#"// received amount of a constant product swap, floor division like amm::swap
+template {name}Swap() {{
+    signal input is_swap_x_to_y;
+    signal input balance_x;
+    signal input balance_y;
+    signal input input_amount;
+    signal output out;
+
+    is_swap_x_to_y * (is_swap_x_to_y - 1) === 0;
+
+    component balance_x_bits = Num2Bits(64);
+    balance_x_bits.in <== balance_x;
+    component balance_y_bits = Num2Bits(64);
+    balance_y_bits.in <== balance_y;
+    component input_amount_bits = Num2Bits(64);
+    input_amount_bits.in <== input_amount;
+
+    signal reserve_in <== is_swap_x_to_y * (balance_x - balance_y) + balance_y;
+    signal reserve_out <== balance_x + balance_y - reserve_in;
+    signal k <== reserve_in * reserve_out;
+    signal new_reserve_in <== reserve_in + input_amount;
+
+    signal new_reserve_out <-- k \ new_reserve_in;
+    signal remainder <-- k % new_reserve_in;
+    k === new_reserve_out * new_reserve_in + remainder;
+
+    component new_reserve_out_bits = Num2Bits(64);
+    new_reserve_out_bits.in <== new_reserve_out;
+    component remainder_bits = Num2Bits(65);
+    remainder_bits.in <== remainder;
+    component remainder_lt = LessThan(65);
+    remainder_lt.in[0] <== remainder;
+    remainder_lt.in[1] <== new_reserve_in;
+    remainder_lt.out === 1;
+
+    out <== reserve_out - new_reserve_out;
+    component out_bits = Num2Bits(64);
+    out_bits.in <== out;
+}}
+"#,
+            name = self.name
+        )
+        .unwrap();
+    }
+
+    fn write_slippage(&self, out: &mut String) {
+        writeln!(
+            out,
+            r#"// amount +/- amount * slippage / 10000 like amm::slippage_bounds, the encoder range checks both
+template {name}Slippage() {{
+    signal input amount;
+    signal input slippage;
+    signal output upper;
+    signal output lower;
+
+    component slippage_bits = Num2Bits(64);
+    slippage_bits.in <== slippage;
+
+    signal scaled <== amount * slippage;
+    signal delta <-- scaled \ 10000;
+    signal remainder <-- scaled % 10000;
+    scaled === delta * 10000 + remainder;
+
+    component delta_bits = Num2Bits(64);
+    delta_bits.in <== delta;
+    component remainder_bits = Num2Bits(14);
+    remainder_bits.in <== remainder;
+    component remainder_lt = LessThan(14);
+    remainder_lt.in[0] <== remainder;
+    remainder_lt.in[1] <== 10000;
+    remainder_lt.out === 1;
+
+    upper <== amount + delta;
+    lower <== amount - delta;
+}}
+"#,
+            name = self.name
+        )
+        .unwrap();
+    }
+
+    fn write_encoder(&self, out: &mut String, weights: &[[u64; AMOUNT_BITS]]) {
+        let rows: Vec<String> = weights.iter().map(|row| format!("[{}]", join(row))).collect();
+
+        writeln!(
+            out,
+            r#"// {encoder} features of the amount, a weighted sum of its bits (LSB first)
+template {name}Encode() {{
+    signal input value;
+    signal output features[{features}];
+
+    var WEIGHTS[{features}][{bits}] = [
+        {rows}
+    ];
+
+    component bits = Num2Bits({bits});
+    bits.in <== value;
+
+    for (var i = 0; i < {features}; i++) {{
+        var feature = 0;
+        for (var k = 0; k < {bits}; k++) {{
+            feature += WEIGHTS[i][k] * bits.out[k];
+        }}
+        features[i] <== feature;
+    }}
+}}
+"#,
+            encoder = self.encoder.name(),
+            name = self.name,
+            features = self.feature_count(),
+            bits = AMOUNT_BITS,
+            rows = rows.join(",\n        ")
+        )
+        .unwrap();
+    }
+
+    fn write_coefficients(&self, out: &mut String) {
+        let bits = self.coefficient_bits;
+        let decode = match self.decoding {
+            Decoding::DropMsb => format!("magnitude * (1 - 2 * hash_bits[i][j].out[{}])", bits - 1),
+            Decoding::TwosComplement => format!("magnitude - hash_bits[i][j].out[{}] * 2 ** {}", bits - 1, bits - 1),
+        };
+
+        writeln!(
+            out,
+            r#"// projection coefficients, the low {bits} bits of Poseidon(salt, i, j + {offset}) decoded as {decoding}
+template {name}Coefficients() {{
+    signal input salt;
+    signal output coefficients[{projections}][{features}];
+
+    component hashes[{projections}][{features}];
+    component hash_bits[{projections}][{features}];
+
+    for (var i = 0; i < {projections}; i++) {{
+        for (var j = 0; j < {features}; j++) {{
+            hashes[i][j] = Poseidon(3);
+            hashes[i][j].inputs[0] <== salt;
+            hashes[i][j].inputs[1] <== i;
+            hashes[i][j].inputs[2] <== j + {offset};
+
+            hash_bits[i][j] = Num2Bits_strict();
+            hash_bits[i][j].in <== hashes[i][j].out;
+
+            var magnitude = 0;
+            for (var k = 0; k < {magnitude_bits}; k++) {{
+                magnitude += hash_bits[i][j].out[k] * 2 ** k;
+            }}
+            coefficients[i][j] <== {decode};
+        }}
+    }}
+}}
+"#,
+            bits = bits,
+            offset = self.input_index_offset,
+            decoding = self.decoding.name(),
+            name = self.name,
+            projections = self.projection_count,
+            features = self.feature_count(),
+            magnitude_bits = bits - 1,
+            decode = decode
+        )
+        .unwrap();
+    }
+
+    fn write_project(&self, out: &mut String) {
+        let (projections, features) = (self.projection_count, self.feature_count());
+
+        match self.salt {
+            SaltHandling::Baked(salt) => {
+                let matrix = self.matrix(salt);
+                let rows: Vec<String> = matrix.rows().map(|row| format!("[{}]", join(row))).collect();
+
+                writeln!(
+                    out,
+                    r#"// projection sums with the coefficients for salt {salt}
+template {name}Project() {{
+    signal input features[{features}];
+    signal output sums[{projections}];
+
+    var COEFFICIENTS[{projections}][{features}] = [
+        {rows}
+    ];
+
+    for (var i = 0; i < {projections}; i++) {{
+        var sum = 0;
+        for (var j = 0; j < {features}; j++) {{
+            sum += COEFFICIENTS[i][j] * features[j];
+        }}
+        sums[i] <== sum;
+    }}
+}}
+"#,
+                    salt = salt.value(),
+                    name = self.name,
+                    features = features,
+                    projections = projections,
+                    rows = rows.join(",\n        ")
+                )
+                .unwrap();
+            }
+            SaltHandling::Private | SaltHandling::Public => {
+                writeln!(
+                    out,
+                    r#"// projection sums, features times coefficients summed in the field
+template {name}Project() {{
+    signal input features[{features}];
+    signal input coefficients[{projections}][{features}];
+    signal output sums[{projections}];
+
+    signal products[{projections}][{features}];
+
+    for (var i = 0; i < {projections}; i++) {{
+        var sum = 0;
+        for (var j = 0; j < {features}; j++) {{
+            products[i][j] <== features[j] * coefficients[i][j];
+            sum += products[i][j];
+        }}
+        sums[i] <== sum;
+    }}
+}}
+"#,
+                    name = self.name,
+                    features = features,
+                    projections = projections
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn write_sign(&self, out: &mut String) {
+        writeln!(
+            out,
+            r#"// hash bit, 1 if the sum is above (p - 1) / 2 i.e. negative
+template {name}Sign() {{
+    signal input in;
+    signal output out;
+
+    component bits = Num2Bits_strict();
+    bits.in <== in;
+
+    component sign = Sign();
+    for (var k = 0; k < 254; k++) {{
+        sign.in[k] <== bits.out[k];
+    }}
+    out <== sign.sign;
+}}
+"#,
+            name = self.name
+        )
+        .unwrap();
+    }
+
+    fn write_main(&self, out: &mut String) {
+        let baked = matches!(self.salt, SaltHandling::Baked(_));
+        let salt_binding = match self.salt {
+            SaltHandling::Baked(salt) => format!("\n    salt === {};\n", salt.value()),
+            _ => "\n    component salt_hash = Poseidon(2);\n    salt_hash.inputs[0] <== salt;\n    salt_hash.inputs[1] <== salt_blinding;\n    salt_commitment <== salt_hash.out;\n".to_string(),
+        };
+        let salt_signals = if baked { "" } else { "\n    signal input salt_blinding;" };
+        let salt_output = if baked { "" } else { "\n    signal output salt_commitment;" };
+        let coefficients = if baked {
+            String::new()
+        } else {
+            "\n    component coefficients = NameCoefficients();\n    coefficients.salt <== salt;\n".replace("Name", &self.name)
+        };
+        let connect_coefficients = if baked {
+            String::new()
+        } else {
+            format!(
+                r#"
+        for (var i = 0; i < {}; i++) {{
+            for (var j = 0; j < {}; j++) {{
+                project[h].coefficients[i][j] <== coefficients.coefficients[i][j];
+            }}
+        }}"#,
+                self.projection_count,
+                self.feature_count()
+            )
+        };
+        let public = if self.salt == SaltHandling::Public { " {public [salt]}" } else { "" };
+
+        write!(
+            out,
+            r#"// base, upper and lower hash of a trade, see get_boundary_hashes
+template {name}() {{
+    signal input salt;{salt_signals}
+    signal input is_swap_x_to_y;
+    signal input balance_x;
+    signal input balance_y;
+    signal input input_amount;
+    signal input slippage;
+{salt_output}
+    signal output base_hash[{projections}];
+    signal output upper_hash[{projections}];
+    signal output lower_hash[{projections}];
+
+    signal base_sum[{projections}];
+    signal upper_sum[{projections}];
+    signal lower_sum[{projections}];
+{salt_binding}
+    component swap = {name}Swap();
+    swap.is_swap_x_to_y <== is_swap_x_to_y;
+    swap.balance_x <== balance_x;
+    swap.balance_y <== balance_y;
+    swap.input_amount <== input_amount;
+
+    component bounds = {name}Slippage();
+    bounds.amount <== swap.out;
+    bounds.slippage <== slippage;
+{coefficients}
+    component encode[3];
+    component project[3];
+    component sign[3][{projections}];
+
+    encode[0] = {name}Encode();
+    encode[0].value <== swap.out;
+    encode[1] = {name}Encode();
+    encode[1].value <== bounds.upper;
+    encode[2] = {name}Encode();
+    encode[2].value <== bounds.lower;
+
+    for (var h = 0; h < 3; h++) {{
+        project[h] = {name}Project();
+        for (var j = 0; j < {features}; j++) {{
+            project[h].features[j] <== encode[h].features[j];
+        }}{connect_coefficients}
+
+        for (var i = 0; i < {projections}; i++) {{
+            sign[h][i] = {name}Sign();
+            sign[h][i].in <== project[h].sums[i];
+        }}
+    }}
+
+    for (var i = 0; i < {projections}; i++) {{
+        base_sum[i] <== project[0].sums[i];
+        upper_sum[i] <== project[1].sums[i];
+        lower_sum[i] <== project[2].sums[i];
+
+        base_hash[i] <== sign[0][i].out;
+        upper_hash[i] <== sign[1][i].out;
+        lower_hash[i] <== sign[2][i].out;
+    }}
+}}
+
+component main{public} = {name}();
+"#,
+            name = self.name,
+            projections = self.projection_count,
+            features = self.feature_count(),
+            salt_signals = salt_signals,
+            salt_output = salt_output,
+            salt_binding = salt_binding,
+            coefficients = coefficients,
+            connect_coefficients = connect_coefficients,
+            public = public
+        )
+        .unwrap();
+    }
+}
+
+/// Coefficient the circuit gets when it keeps only the low `bits` bits of the projection
+/// and decodes them with `decoding` at that width. At 64 bits this is `coefficient`.
+pub fn narrow_coefficient(coefficient: i64, decoding: Decoding, bits: u32) -> i64 {
+    // raw projection bits back from the decoded value, drop MSB keeps sign and magnitude apart
+    let raw = match decoding {
+        Decoding::DropMsb => coefficient.unsigned_abs() | ((coefficient < 0) as u64) << 63,
+        Decoding::TwosComplement => coefficient as u64,
+    };
+
+    let is_negative = (raw >> (bits - 1)) & 1 == 1;
+    let magnitude = raw & ((1u64 << (bits - 1)) - 1);
+
+    match (decoding, is_negative) {
+        (_, false) => magnitude as i64,
+        (Decoding::DropMsb, true) => -(magnitude as i64),
+        (_, true) => (magnitude as i128 - (1i128 << (bits - 1))) as i64,
+    }
+}
+
+fn optional_u64(json: &Value, field: &'static str) -> Result<Option<u64>, CodegenError> {
+    match &json[field] {
+        Value::Null => Ok(None),
+        value => value.as_u64().map(Some).ok_or(CodegenError::BadField(field)),
+    }
+}
+
+/// `"test"` for [`Salt::test`] or a non zero salt.
+fn salt_value(value: &Value) -> Result<Salt, CodegenError> {
+    match value {
+        Value::String(test) if test == "test" => Ok(Salt::test()),
+        value => Ok(Salt::unblinded(value.as_u64().ok_or(CodegenError::BadField("salt"))?)?),
+    }
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(T::to_string).collect::<Vec<_>>().join(", ")
+}
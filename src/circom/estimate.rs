@@ -0,0 +1,97 @@
+//! Analytical constraint count of the template [`codegen`](super::codegen) generates, to
+//! weigh precision against proving cost before compiling anything. Counts are non-linear
+//! constraints, i.e. what `circom --O2` keeps. Compare with the `.r1cs` report once a
+//! configuration is compiled.
+
+use std::fmt;
+
+use super::codegen::{CircuitConfig, SaltHandling};
+use super::r1cs::OTHER_SECTION;
+use crate::projection::Decoding;
+
+/// circomlib `Poseidon(3)`: 8 full rounds of 4 S-boxes and 56 partial rounds of one,
+/// 3 constraints per x^5 S-box.
+pub const POSEIDON3_CONSTRAINTS: u64 = (8 * 4 + 56) * 3;
+/// circomlib `Poseidon(2)`, the salt commitment: 8 full rounds of 3 S-boxes and 57 partial rounds.
+pub const POSEIDON2_CONSTRAINTS: u64 = (8 * 3 + 57) * 3;
+/// circomlib `CompConstant`: one product per bit pair plus `Num2Bits(135)`.
+pub const COMP_CONSTANT_CONSTRAINTS: u64 = 127 + 135;
+/// circomlib `Num2Bits_strict`: `Num2Bits(254)` plus the `AliasCheck` comparison.
+pub const NUM2BITS_STRICT_CONSTRAINTS: u64 = 254 + COMP_CONSTANT_CONSTRAINTS;
+
+/// Fixed cost of the swap: booleanity, three 64 bit inputs, two products, the division
+/// check, the quotient, the remainder and `LessThan(65)`, the output range check.
+const SWAP_CONSTRAINTS: u64 = 1 + 3 * 64 + 2 + 1 + 64 + 65 + 66 + 64;
+/// Slippage: range checks of slippage and delta, the product, the remainder and `LessThan(14)`.
+const SLIPPAGE_CONSTRAINTS: u64 = 64 + 1 + 64 + 14 + 15;
+
+/// Estimated constraints per section, named like the `.r1cs` report.
+#[derive(Clone, Debug)]
+pub struct ConstraintEstimate {
+    pub projection_count: u64,
+    pub feature_count: u64,
+    pub sections: Vec<(&'static str, u64)>,
+    pub public_outputs: u64,
+    pub public_inputs: u64,
+    pub private_inputs: u64,
+}
+
+impl ConstraintEstimate {
+    pub fn constraints(&self) -> u64 {
+        self.sections.iter().map(|(_, constraints)| constraints).sum()
+    }
+}
+
+impl fmt::Display for ConstraintEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} projections x {} features: ~{} constraints | public outputs: {} | public inputs: {} | private inputs: {}",
+            self.projection_count,
+            self.feature_count,
+            self.constraints(),
+            self.public_outputs,
+            self.public_inputs,
+            self.private_inputs
+        )?;
+
+        for (name, constraints) in &self.sections {
+            writeln!(f, "  {:<15} {:>12} constraints", name, constraints)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Constraint count of the template for `config`.
+pub fn estimate(config: &CircuitConfig) -> ConstraintEstimate {
+    let projections = config.projection_count;
+    let features = config.feature_count() as u64;
+    let baked = matches!(config.salt, SaltHandling::Baked(_));
+
+    // two's complement decoding is linear in the bits, drop MSB multiplies by the sign
+    let decode = (config.decoding == Decoding::DropMsb) as u64;
+    let per_coefficient = POSEIDON3_CONSTRAINTS + NUM2BITS_STRICT_CONSTRAINTS + decode;
+
+    // baked coefficients are constants, the products become linear
+    let coefficients = if baked { 0 } else { projections * features * per_coefficient };
+    let products = if baked { 0 } else { 3 * projections * features };
+
+    ConstraintEstimate {
+        projection_count: projections,
+        feature_count: features,
+        sections: vec![
+            ("amm", SWAP_CONSTRAINTS + SLIPPAGE_CONSTRAINTS),
+            ("encoder", 3 * 64),
+            ("projections", coefficients),
+            ("multiplication", products),
+            ("comparison", 3 * projections * (NUM2BITS_STRICT_CONSTRAINTS + COMP_CONSTANT_CONSTRAINTS)),
+            ("salt commitment", if baked { 0 } else { POSEIDON2_CONSTRAINTS }),
+            (OTHER_SECTION, 0),
+        ],
+        public_outputs: 3 * projections + !baked as u64,
+        public_inputs: (config.salt == SaltHandling::Public) as u64,
+        // the salt blinding is the seventh input
+        private_inputs: 6 + !baked as u64 - (config.salt == SaltHandling::Public) as u64,
+    }
+}
@@ -0,0 +1,175 @@
+//! Circuit input (`input.json`) and expected public outputs for a trade scenario, so
+//! circuit tests can be generated from Rust instead of copying values from logs.
+
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::codegen::narrow_coefficient;
+use super::witness::{CircomFileError, SymbolMap, Witness};
+use crate::boundary::get_boundary_hashes;
+use crate::encoder::{FeatureEncoder, Features};
+use crate::field::Fr;
+use crate::hash::LshHash;
+use crate::lsh::LshConfig;
+use crate::matrix::ProjectionMatrix;
+use crate::salt::Salt;
+
+/// The values [`get_boundary_hashes`](crate::get_boundary_hashes) takes, plus how the
+/// received amounts are encoded and hashed.
+#[derive(Debug)]
+pub struct Scenario {
+    pub is_swap_x_to_y: bool,
+    pub balance_x: u64,
+    pub balance_y: u64,
+    pub input_amount: u64,
+    /// 10,000 = 100%
+    pub slippage: u64,
+    pub encoder: Box<dyn FeatureEncoder>,
+    pub config: LshConfig,
+    /// Low bits of every projection the circuit keeps, 64 is the full coefficient, see
+    /// [`narrow_coefficient`].
+    pub coefficient_bits: u32,
+}
+
+/// Everything the circuit is expected to compute for a [`Scenario`].
+#[derive(Clone, Debug)]
+pub struct ScenarioHashes {
+    pub output: u64,
+    pub upper_output: u64,
+    pub lower_output: u64,
+    pub base_features: Vec<u64>,
+    pub upper_features: Vec<u64>,
+    pub lower_features: Vec<u64>,
+    pub base_hash: LshHash,
+    pub upper_hash: LshHash,
+    pub lower_hash: LshHash,
+}
+
+impl Scenario {
+    /// Scenario whose `input.json` produced `witness`, the salt and its blinding are read
+    /// from the witness too so only the LSH parameters the circuit was compiled with are
+    /// needed. A circuit without a `salt_blinding` input has blinding 0.
+    pub fn from_witness(
+        witness: &Witness,
+        symbols: &SymbolMap,
+        encoder: Box<dyn FeatureEncoder>,
+        config: LshConfig,
+        coefficient_bits: u32,
+    ) -> Result<Self, CircomFileError> {
+        let signal = |name: &str| witness.signal_u64(symbols, &format!("main.{}", name));
+        let salt_blinding = witness.signal(symbols, "main.salt_blinding").unwrap_or(Fr::from(0u64));
+
+        Ok(Scenario {
+            is_swap_x_to_y: witness.signal_bit(symbols, "main.is_swap_x_to_y")?,
+            balance_x: signal("balance_x")?,
+            balance_y: signal("balance_y")?,
+            input_amount: signal("input_amount")?,
+            slippage: signal("slippage")?,
+            encoder,
+            config: LshConfig { salt: Salt::recorded(signal("salt")?, salt_blinding), ..config },
+            coefficient_bits,
+        })
+    }
+
+    /// Integer features of `value`, panics for float encoders since the circuit can't use them.
+    pub fn features(&self, value: u64) -> Vec<u64> {
+        match self.encoder.encode(value) {
+            Features::Integer(features) => features,
+            Features::Float(_) => panic!("{} produces float features, the circuit needs integers", self.encoder.name()),
+        }
+    }
+
+    /// Coefficients the circuit hashes with, the ones `config` derives narrowed to
+    /// `coefficient_bits`.
+    pub fn matrix(&self) -> Arc<ProjectionMatrix> {
+        let matrix = ProjectionMatrix::cached(&self.config, self.encoder.dimension() as u64);
+
+        match self.coefficient_bits {
+            64 => matrix,
+            bits => Arc::new(matrix.map_coefficients(|coefficient| narrow_coefficient(coefficient, self.config.decoding, bits))),
+        }
+    }
+
+    /// Runs [`get_boundary_hashes`] on the scenario with the field backend, i.e. the
+    /// way the circuit hashes, and adds the hash of the unslipped output.
+    pub fn evaluate(&self) -> ScenarioHashes {
+        self.evaluate_with(&self.matrix())
+    }
+
+    /// [`Scenario::evaluate`] with the coefficients of `matrix` instead of the ones `config` derives.
+    pub fn evaluate_with(&self, matrix: &ProjectionMatrix) -> ScenarioHashes {
+        let lsh = |value: u64| matrix.hash_field(&self.features(value));
+
+        let ([upper_hash, lower_hash], upper_output, lower_output, output) =
+            get_boundary_hashes(self.is_swap_x_to_y, self.balance_x, self.balance_y, self.input_amount, self.slippage, lsh);
+
+        ScenarioHashes {
+            output,
+            upper_output,
+            lower_output,
+            base_features: self.features(output),
+            upper_features: self.features(upper_output),
+            lower_features: self.features(lower_output),
+            base_hash: lsh(output),
+            upper_hash,
+            lower_hash,
+        }
+    }
+
+    /// `input.json` for snarkjs, every value as a decimal string.
+    pub fn input_json(&self) -> Value {
+        json!({
+            "salt": self.config.salt.value().to_string(),
+            "salt_blinding": self.config.salt.blinding().to_string(),
+            "is_swap_x_to_y": (self.is_swap_x_to_y as u8).to_string(),
+            "balance_x": self.balance_x.to_string(),
+            "balance_y": self.balance_y.to_string(),
+            "input_amount": self.input_amount.to_string(),
+            "slippage": self.slippage.to_string(),
+        })
+    }
+
+    /// Expected outputs, plus the intermediate amounts and the LSH parameters they were
+    /// computed with. `public` is the flat signal list in snarkjs `public.json` order, the
+    /// salt commitment then the hash bits.
+    pub fn expected_json(&self) -> Value {
+        self.hashes_json(&self.evaluate())
+    }
+
+    /// [`Scenario::expected_json`] for already evaluated `hashes`.
+    pub fn hashes_json(&self, hashes: &ScenarioHashes) -> Value {
+        let bits = |hash: &LshHash| hash.iter().map(|bit| (bit as u8).to_string()).collect::<Vec<String>>();
+
+        let salt_commitment = self.config.salt.commitment().to_string();
+        let public: Vec<String> = std::iter::once(salt_commitment.clone())
+            .chain([&hashes.base_hash, &hashes.upper_hash, &hashes.lower_hash].into_iter().flat_map(bits))
+            .collect();
+
+        json!({
+            "encoder": self.encoder.name(),
+            "projection_count": self.config.projection_count,
+            "input_index_offset": self.config.input_index_offset,
+            "source": self.config.source.name(),
+            "decoding": self.config.decoding.name(),
+            "coefficient_bits": self.coefficient_bits,
+            "output": hashes.output.to_string(),
+            "upper_output": hashes.upper_output.to_string(),
+            "lower_output": hashes.lower_output.to_string(),
+            "salt_commitment": salt_commitment,
+            "base_hash": bits(&hashes.base_hash),
+            "upper_hash": bits(&hashes.upper_hash),
+            "lower_hash": bits(&hashes.lower_hash),
+            "public": public,
+        })
+    }
+
+    /// Writes `input.json` and `expected.json` into `dir`.
+    pub fn export(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("input.json"), serde_json::to_string_pretty(&self.input_json())? + "\n")?;
+        fs::write(dir.join("expected.json"), serde_json::to_string_pretty(&self.expected_json())? + "\n")
+    }
+}
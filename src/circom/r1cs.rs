@@ -0,0 +1,208 @@
+//! Reader for the circom `.r1cs` constraint system (iden3 binary format, version 1) and
+//! a constraint count report per part of the LSH circuit.
+//!
+//! Sections: 1 is the header (`n8 u32`, prime, wire count u32, public outputs u32,
+//! public inputs u32, private inputs u32, label count u64, constraint count u32), 2 the
+//! constraints (`A`, `B`, `C` linear combinations of `term count u32` and `wire u32,
+//! coefficient n8` terms) and 3 the label of every wire.
+//!
+//! Constraints are attributed with the `.sym` names of their wires, see [`SECTIONS`].
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::witness::{check_prime, read_sections, section, CircomFileError, Reader, SymbolMap};
+
+pub const MAGIC: [u8; 4] = *b"r1cs";
+pub const VERSION: u32 = 1;
+
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+
+/// Parts of the generated circuit (see [`codegen`](super::codegen)) and the signal name
+/// prefixes that belong to them. Anything else is `other`.
+pub const SECTIONS: [(&str, &[&str]); 6] = [
+    ("amm", &["main.swap.", "main.bounds."]),
+    ("encoder", &["main.encode["]),
+    ("projections", &["main.coefficients."]),
+    ("multiplication", &["main.project["]),
+    ("comparison", &["main.sign["]),
+    ("salt commitment", &["main.salt_hash."]),
+];
+
+/// Name of the catch-all section, always last in a report.
+pub const OTHER_SECTION: &str = "other";
+
+/// Header of a `.r1cs` file plus its undecoded constraints.
+#[derive(Clone, Debug)]
+pub struct R1cs<'a> {
+    pub wire_count: u32,
+    pub public_outputs: u32,
+    pub public_inputs: u32,
+    pub private_inputs: u32,
+    pub label_count: u64,
+    pub constraint_count: u32,
+    field_size: usize,
+    constraints: &'a [u8],
+}
+
+/// Wires of one constraint `A * B = C`, coefficients are skipped.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintWires {
+    pub a: Vec<u32>,
+    pub b: Vec<u32>,
+    pub c: Vec<u32>,
+}
+
+impl ConstraintWires {
+    /// `A` or `B` empty, i.e. the constraint is linear and `--O2` would remove it.
+    pub fn is_linear(&self) -> bool {
+        self.a.is_empty() || self.b.is_empty()
+    }
+}
+
+impl<'a> R1cs<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, CircomFileError> {
+        let sections = read_sections(bytes, &MAGIC, &[VERSION])?;
+
+        let mut header = Reader::new(section(&sections, HEADER_SECTION)?);
+        let field_size = header.u32()? as usize;
+        check_prime(header.take(field_size)?)?;
+
+        Ok(R1cs {
+            wire_count: header.u32()?,
+            public_outputs: header.u32()?,
+            public_inputs: header.u32()?,
+            private_inputs: header.u32()?,
+            label_count: header.u64()?,
+            constraint_count: header.u32()?,
+            field_size,
+            constraints: section(&sections, CONSTRAINTS_SECTION)?,
+        })
+    }
+
+    /// Decodes the constraints one at a time, the file is not held decoded in memory.
+    pub fn constraints(&self) -> impl Iterator<Item = Result<ConstraintWires, CircomFileError>> + '_ {
+        let mut reader = Reader::new(self.constraints);
+
+        (0..self.constraint_count).map(move |_| {
+            let mut linear_combination = || -> Result<Vec<u32>, CircomFileError> {
+                let terms = reader.u32()?;
+                (0..terms)
+                    .map(|_| {
+                        let wire = reader.u32()?;
+                        reader.take(self.field_size)?;
+                        Ok(wire)
+                    })
+                    .collect()
+            };
+
+            Ok(ConstraintWires { a: linear_combination()?, b: linear_combination()?, c: linear_combination()? })
+        })
+    }
+}
+
+/// Constraints and wires of one [`SECTIONS`] entry.
+#[derive(Clone, Debug, Default)]
+pub struct SectionCount {
+    pub name: &'static str,
+    pub constraints: u64,
+    pub linear_constraints: u64,
+    pub wires: u64,
+}
+
+/// Result of [`constraint_report`].
+#[derive(Clone, Debug)]
+pub struct ConstraintReport {
+    pub constraints: u64,
+    pub linear_constraints: u64,
+    pub wires: u32,
+    pub public_outputs: u32,
+    pub public_inputs: u32,
+    pub private_inputs: u32,
+    /// Per section, empty without a `.sym` file.
+    pub sections: Vec<SectionCount>,
+}
+
+impl fmt::Display for ConstraintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "constraints: {} ({} linear)", self.constraints, self.linear_constraints)?;
+        writeln!(
+            f,
+            "wires: {} | public outputs: {} | public inputs: {} | private inputs: {}",
+            self.wires, self.public_outputs, self.public_inputs, self.private_inputs
+        )?;
+
+        for section in &self.sections {
+            writeln!(
+                f,
+                "  {:<15} {:>12} constraints {:>12} linear {:>12} wires",
+                section.name, section.constraints, section.linear_constraints, section.wires
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Section of a signal name, see [`SECTIONS`].
+pub fn section_of(name: &str) -> &'static str {
+    SECTIONS
+        .iter()
+        .find(|(_, prefixes)| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+        .map_or(OTHER_SECTION, |(section, _)| section)
+}
+
+/// Counts the constraints of `r1cs`, per section if `symbols` is given. A constraint
+/// belongs to the section of the first named wire of `C`, then `A`, then `B`.
+pub fn constraint_report(r1cs: &R1cs, symbols: Option<&SymbolMap>) -> Result<ConstraintReport, CircomFileError> {
+    let names: Vec<&'static str> = SECTIONS.iter().map(|(name, _)| *name).chain([OTHER_SECTION]).collect();
+    let mut sections: Vec<SectionCount> = names.iter().map(|&name| SectionCount { name, ..Default::default() }).collect();
+
+    // a wire can have several names (component inputs are aliases), the deepest one wins
+    let mut wire_names: HashMap<u32, &str> = HashMap::new();
+    for (name, &wire) in symbols.iter().flat_map(|symbols| &symbols.indexes) {
+        let entry = wire_names.entry(wire as u32).or_insert(name);
+        if (name.matches('.').count(), name.as_str()) > (entry.matches('.').count(), *entry) {
+            *entry = name;
+        }
+    }
+    let wire_sections: HashMap<u32, usize> = wire_names
+        .iter()
+        .map(|(&wire, name)| (wire, names.iter().position(|&section| section == section_of(name)).unwrap()))
+        .collect();
+
+    for &section in wire_sections.values() {
+        sections[section].wires += 1;
+    }
+
+    let (mut constraints, mut linear_constraints) = (0, 0);
+    for constraint in r1cs.constraints() {
+        let constraint = constraint?;
+        let is_linear = constraint.is_linear();
+
+        constraints += 1;
+        linear_constraints += is_linear as u64;
+
+        // wire 0 is the constant one
+        let section = [&constraint.c, &constraint.a, &constraint.b]
+            .into_iter()
+            .flatten()
+            .find_map(|wire| wire_sections.get(wire).filter(|_| *wire != 0))
+            .copied()
+            .unwrap_or(names.len() - 1);
+
+        sections[section].constraints += 1;
+        sections[section].linear_constraints += is_linear as u64;
+    }
+
+    Ok(ConstraintReport {
+        constraints,
+        linear_constraints,
+        wires: r1cs.wire_count,
+        public_outputs: r1cs.public_outputs,
+        public_inputs: r1cs.public_inputs,
+        private_inputs: r1cs.private_inputs,
+        sections: if symbols.is_some() { sections } else { Vec::new() },
+    })
+}
@@ -0,0 +1,238 @@
+//! Readers for the circom `.wtns` witness (snarkjs binary format, version 2) and the
+//! `.sym` signal map the compiler writes next to the `.r1cs`.
+//!
+//! `.wtns` layout, all integers little endian: magic `wtns`, version u32, section count
+//! u32, then sections of `type u32, size u64, data`. Section 1 is `n8 u32`, the prime as
+//! `n8` bytes and the witness count u32. Section 2 is the witness values, `n8` bytes each.
+//!
+//! `.sym` is one `label index,witness index,component index,name` line per signal, a
+//! witness index of -1 means the optimizer removed the signal.
+
+use ark_ff::{BigInteger, PrimeField};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::field::Fr;
+
+pub const MAGIC: [u8; 4] = *b"wtns";
+pub const VERSION: u32 = 2;
+
+const HEADER_SECTION: u32 = 1;
+const VALUES_SECTION: u32 = 2;
+
+#[derive(Debug)]
+pub enum CircomFileError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    MissingSection(u32),
+    Truncated,
+    /// File is for another field than BN254.
+    WrongPrime,
+    /// Unparseable `.sym` line, 1 based.
+    BadSymbolLine(usize),
+    MissingSignal(String),
+    /// Signal that should be boolean holds something else.
+    NotABit(String),
+    /// Signal that should fit a u64 does not.
+    NotAU64(String),
+}
+
+impl fmt::Display for CircomFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircomFileError::Io(err) => write!(f, "io error: {}", err),
+            CircomFileError::BadMagic => write!(f, "wrong file magic"),
+            CircomFileError::UnsupportedVersion(version) => write!(f, "unsupported file version {}", version),
+            CircomFileError::MissingSection(id) => write!(f, "file has no section {}", id),
+            CircomFileError::Truncated => write!(f, "file is truncated"),
+            CircomFileError::WrongPrime => write!(f, "file is not over the BN254 scalar field"),
+            CircomFileError::BadSymbolLine(line) => write!(f, "cannot parse symbol line {}", line),
+            CircomFileError::MissingSignal(name) => write!(f, "signal '{}' is not in the witness", name),
+            CircomFileError::NotABit(name) => write!(f, "signal '{}' is not 0 or 1", name),
+            CircomFileError::NotAU64(name) => write!(f, "signal '{}' does not fit a u64", name),
+        }
+    }
+}
+
+impl std::error::Error for CircomFileError {}
+
+impl From<io::Error> for CircomFileError {
+    fn from(err: io::Error) -> Self {
+        CircomFileError::Io(err)
+    }
+}
+
+/// Witness values in wire order, index 0 is the constant 1.
+#[derive(Clone, Debug)]
+pub struct Witness {
+    pub values: Vec<Fr>,
+}
+
+/// Signal name (e.g. `main.base_hash[3]`) to witness index.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolMap {
+    pub indexes: HashMap<String, usize>,
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, offset: 0 }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], CircomFileError> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or(CircomFileError::Truncated)?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+
+        Ok(slice)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, CircomFileError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, CircomFileError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Splits an iden3 binary file (`.wtns`, `.r1cs`, `.zkey`) into its sections, checking
+/// the magic and version. Sections are returned in file order as `(type, data)`.
+pub(crate) fn read_sections<'a>(bytes: &'a [u8], magic: &[u8; 4], versions: &[u32]) -> Result<Vec<(u32, &'a [u8])>, CircomFileError> {
+    let mut reader = Reader::new(bytes);
+
+    if reader.take(4)? != magic {
+        return Err(CircomFileError::BadMagic);
+    }
+
+    let version = reader.u32()?;
+    if !versions.contains(&version) {
+        return Err(CircomFileError::UnsupportedVersion(version));
+    }
+
+    let section_count = reader.u32()?;
+    // a section header is 12 bytes, don't trust the count for the allocation
+    let mut sections = Vec::with_capacity((section_count as usize).min(bytes.len() / 12));
+
+    for _ in 0..section_count {
+        let id = reader.u32()?;
+        let size = reader.u64()?;
+        let data = reader.take(usize::try_from(size).map_err(|_| CircomFileError::Truncated)?)?;
+        sections.push((id, data));
+    }
+
+    Ok(sections)
+}
+
+/// Data of the first section of type `id`.
+pub(crate) fn section<'a>(sections: &[(u32, &'a [u8])], id: u32) -> Result<&'a [u8], CircomFileError> {
+    sections.iter().find(|(section_id, _)| *section_id == id).map(|(_, data)| *data).ok_or(CircomFileError::MissingSection(id))
+}
+
+/// Checks that a little endian `n8` byte prime is the BN254 scalar field modulus.
+pub(crate) fn check_prime(prime: &[u8]) -> Result<(), CircomFileError> {
+    let modulus = Fr::MODULUS.to_bytes_le();
+
+    if prime.len() >= modulus.len() && prime[..modulus.len()] == modulus[..] && prime[modulus.len()..].iter().all(|&byte| byte == 0) {
+        Ok(())
+    } else {
+        Err(CircomFileError::WrongPrime)
+    }
+}
+
+impl Witness {
+    pub fn load(path: &Path) -> Result<Self, CircomFileError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, CircomFileError> {
+        let sections = read_sections(bytes, &MAGIC, &[VERSION])?;
+
+        let mut header = Reader::new(section(&sections, HEADER_SECTION)?);
+        let n8 = header.u32()? as usize;
+        check_prime(header.take(n8)?)?;
+        let count = header.u32()? as usize;
+
+        let data = section(&sections, VALUES_SECTION)?;
+        if count.checked_mul(n8) != Some(data.len()) {
+            return Err(CircomFileError::Truncated);
+        }
+
+        let values = data.chunks_exact(n8).map(Fr::from_le_bytes_mod_order).collect();
+
+        Ok(Witness { values })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Fr> {
+        self.values.get(index)
+    }
+
+    /// Value of the signal `name`.
+    pub fn signal(&self, symbols: &SymbolMap, name: &str) -> Result<Fr, CircomFileError> {
+        symbols.index(name).and_then(|index| self.get(index)).copied().ok_or_else(|| CircomFileError::MissingSignal(name.to_string()))
+    }
+
+    /// Value of a signal that has to fit a u64, e.g. an input amount.
+    pub fn signal_u64(&self, symbols: &SymbolMap, name: &str) -> Result<u64, CircomFileError> {
+        let value = self.signal(symbols, name)?.into_bigint();
+
+        if value.as_ref()[1..].iter().any(|&limb| limb != 0) {
+            return Err(CircomFileError::NotAU64(name.to_string()));
+        }
+
+        Ok(value.as_ref()[0])
+    }
+
+    /// Value of a boolean signal.
+    pub fn signal_bit(&self, symbols: &SymbolMap, name: &str) -> Result<bool, CircomFileError> {
+        let value = self.signal(symbols, name)?;
+
+        if value == Fr::from(0u64) {
+            Ok(false)
+        } else if value == Fr::from(1u64) {
+            Ok(true)
+        } else {
+            Err(CircomFileError::NotABit(name.to_string()))
+        }
+    }
+}
+
+impl SymbolMap {
+    pub fn load(path: &Path) -> Result<Self, CircomFileError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, CircomFileError> {
+        let mut indexes = HashMap::new();
+
+        for (line_index, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let mut fields = line.splitn(4, ',');
+            let witness_index = fields.nth(1).and_then(|field| field.trim().parse::<i64>().ok());
+            let name = fields.nth(1).map(str::trim);
+
+            match (witness_index, name) {
+                (Some(witness_index), Some(name)) if witness_index >= 0 => {
+                    indexes.insert(name.to_string(), witness_index as usize);
+                }
+                // removed by the optimizer
+                (Some(_), Some(_)) => {}
+                _ => return Err(CircomFileError::BadSymbolLine(line_index + 1)),
+            }
+        }
+
+        Ok(SymbolMap { indexes })
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indexes.get(name).copied()
+    }
+}
@@ -0,0 +1,171 @@
+//! Slippage commitments: before trading, the trader publishes only the hashes of its
+//! slippage boundaries (see [`get_pool_boundary_hashes`]), and the executed output is later
+//! checked against them with a [`DecisionRule`]. The default is the rule of the v4-v6
+//! experiments' `*_distance_to_upper >= boundary_distance` columns:
+//!
+//! - the boundary distance is the Hamming distance between the upper and lower hashes
+//! - an output is accepted when its hash is closer than that to both of them
+//!
+//! Identical boundary hashes accept nothing under it, there is no output strictly closer than 0.
+//!
+//! The commitment also carries the [`Salt::commitment`] of the salt the hashes were made
+//! with, and verification rejects any other salt.
+
+use std::fmt;
+
+use crate::amm::Pool;
+use crate::boundary::get_pool_boundary_hashes;
+use crate::encoder::FeatureEncoder;
+use crate::hash::LshHash;
+use crate::field::Fr;
+use crate::lsh::{hash_value, LshConfig};
+use crate::salt::Salt;
+
+pub mod rule;
+
+pub use rule::{evaluate_rules, DecisionRule, RuleEvaluation, DEFAULT_RULES};
+
+/// The trade a commitment is made for.
+#[derive(Clone, Copy, Debug)]
+pub struct Scenario<'a> {
+    pub pool: &'a dyn Pool,
+    pub is_swap_x_to_y: bool,
+    pub input_amount: u64,
+}
+
+/// Encoder, LSH settings and decision rule shared by the trader and the verifier. The
+/// salt of `config` is ignored, every commitment brings its own.
+#[derive(Debug)]
+pub struct CommitmentScheme {
+    pub encoder: Box<dyn FeatureEncoder>,
+    pub config: LshConfig,
+    pub rule: DecisionRule,
+}
+
+/// What the trader publishes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlippageCommitment {
+    pub upper_hash: LshHash,
+    pub lower_hash: LshHash,
+    /// See [`Salt::commitment`].
+    pub salt_commitment: Fr,
+}
+
+/// Hamming distances the rule looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Distances {
+    pub to_upper: u32,
+    pub to_lower: u32,
+    /// Between the upper and lower hashes.
+    pub boundary: u32,
+}
+
+/// Why an output was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The upper and lower hashes are identical.
+    IdenticalBoundaries,
+    /// At least the boundary distance away from the upper hash.
+    FarFromUpper,
+    /// At least the boundary distance away from the lower hash.
+    FarFromLower,
+    /// At least the boundary distance away from both.
+    FarFromBoth,
+    /// The detour through the output's hash is longer than the rule allows.
+    DetourTooLong,
+    /// The salt does not open the commitment's salt commitment.
+    SaltMismatch,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Accept(Distances),
+    Reject(Distances, RejectReason),
+}
+
+impl Distances {
+    pub fn new(upper_hash: &LshHash, lower_hash: &LshHash, hash: &LshHash) -> Self {
+        Distances {
+            to_upper: upper_hash.hamming_distance(hash),
+            to_lower: lower_hash.hamming_distance(hash),
+            boundary: upper_hash.hamming_distance(lower_hash),
+        }
+    }
+}
+
+impl Verdict {
+    /// Applies `rule` to `hash`.
+    pub fn new(rule: DecisionRule, upper_hash: &LshHash, lower_hash: &LshHash, hash: &LshHash) -> Self {
+        rule.verdict(Distances::new(upper_hash, lower_hash, hash))
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Verdict::Accept(_))
+    }
+
+    pub fn distances(&self) -> Distances {
+        match self {
+            Verdict::Accept(distances) | Verdict::Reject(distances, _) => *distances,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let distances = self.distances();
+
+        match self {
+            Verdict::Accept(_) => write!(f, "accept")?,
+            Verdict::Reject(_, reason) => write!(f, "reject ({:?})", reason)?,
+        }
+
+        write!(f, " | to upper {} | to lower {} | boundary distance {}", distances.to_upper, distances.to_lower, distances.boundary)
+    }
+}
+
+impl CommitmentScheme {
+    /// Scheme with the experiments' [`DecisionRule::PerSide`] rule.
+    pub fn new(encoder: Box<dyn FeatureEncoder>, config: LshConfig) -> Self {
+        CommitmentScheme { encoder, config, rule: DecisionRule::PerSide }
+    }
+
+    /// Hash of `output` under `salt`.
+    pub fn hash(&self, output: u64, salt: &Salt) -> LshHash {
+        hash_value(self.encoder.as_ref(), output, &LshConfig { salt: *salt, ..self.config })
+    }
+
+    /// Commits to the slippage boundaries of `scenario`, `None` if its pool can't fill the trade.
+    pub fn commit(&self, scenario: &Scenario, slippage_bps: u64, salt: &Salt) -> Option<SlippageCommitment> {
+        let ([upper_hash, lower_hash], ..) =
+            get_pool_boundary_hashes(scenario.pool, scenario.is_swap_x_to_y, scenario.input_amount, slippage_bps, |output| self.hash(output, salt))?;
+
+        Some(SlippageCommitment { upper_hash, lower_hash, salt_commitment: salt.commitment() })
+    }
+
+    /// Checks the executed output against `commitment`, `salt` being the one it was made with.
+    pub fn verify(&self, commitment: &SlippageCommitment, executed_output: u64, salt: &Salt) -> Verdict {
+        let verdict = commitment.verify_hash(self.rule, &self.hash(executed_output, salt));
+
+        if salt.commitment() != commitment.salt_commitment {
+            return Verdict::Reject(verdict.distances(), RejectReason::SaltMismatch);
+        }
+
+        verdict
+    }
+
+    /// [`CommitmentScheme::verify`] for every `(commitment, executed output, salt)`, in order.
+    pub fn verify_batch<'a>(&self, executions: impl IntoIterator<Item = (&'a SlippageCommitment, u64, &'a Salt)>) -> Vec<Verdict> {
+        executions.into_iter().map(|(commitment, executed_output, salt)| self.verify(commitment, executed_output, salt)).collect()
+    }
+}
+
+impl SlippageCommitment {
+    pub fn boundary_distance(&self) -> u32 {
+        self.upper_hash.hamming_distance(&self.lower_hash)
+    }
+
+    /// `rule` applied to an already computed hash of the executed output, the salt is not checked.
+    pub fn verify_hash(&self, rule: DecisionRule, hash: &LshHash) -> Verdict {
+        Verdict::new(rule, &self.upper_hash, &self.lower_hash, hash)
+    }
+}
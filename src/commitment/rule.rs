@@ -0,0 +1,149 @@
+//! Decision rules for "is the executed output inside the committed boundaries", judged
+//! only from the three Hamming distances, and their false accept and false reject rates
+//! over a sweep of outputs whose true position is known.
+
+use std::fmt;
+
+use super::{CommitmentScheme, Distances, RejectReason, Scenario, Verdict};
+use crate::amm::slippage_bounds;
+use crate::salt::Salt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecisionRule {
+    /// The experiments' rule: closer than the boundary distance to both boundary hashes.
+    PerSide,
+    /// `to_upper + to_lower <= boundary + tolerance`: the output's hash is (nearly) on a
+    /// shortest path between the boundary hashes, as it is for outputs between them.
+    Triangle { tolerance: u32 },
+    /// `100 * (to_upper + to_lower) <= max_percent * boundary`: the triangle rule with a
+    /// tolerance that scales with the boundary distance.
+    Ratio { max_percent: u32 },
+    /// `max(to_upper, to_lower) + margin <= boundary`: the per-side rule with a margin, a
+    /// margin of 1 is [`DecisionRule::PerSide`] and negative margins loosen it.
+    Margin { margin: i32 },
+}
+
+/// Rules [`evaluate_rules`] compares by default.
+pub const DEFAULT_RULES: [DecisionRule; 9] = [
+    DecisionRule::PerSide,
+    DecisionRule::Triangle { tolerance: 0 },
+    DecisionRule::Triangle { tolerance: 2 },
+    DecisionRule::Triangle { tolerance: 8 },
+    DecisionRule::Ratio { max_percent: 110 },
+    DecisionRule::Ratio { max_percent: 125 },
+    DecisionRule::Ratio { max_percent: 150 },
+    DecisionRule::Margin { margin: -2 },
+    DecisionRule::Margin { margin: 4 },
+];
+
+impl DecisionRule {
+    pub fn verdict(&self, distances: Distances) -> Verdict {
+        let (to_upper, to_lower, boundary) = (distances.to_upper as i64, distances.to_lower as i64, distances.boundary as i64);
+
+        let per_side = |margin: i64| match (to_upper + margin > boundary, to_lower + margin > boundary) {
+            (false, false) => Verdict::Accept(distances),
+            _ if boundary == 0 => Verdict::Reject(distances, RejectReason::IdenticalBoundaries),
+            (true, true) => Verdict::Reject(distances, RejectReason::FarFromBoth),
+            (true, false) => Verdict::Reject(distances, RejectReason::FarFromUpper),
+            (false, true) => Verdict::Reject(distances, RejectReason::FarFromLower),
+        };
+        let detour = |accepted: bool| if accepted { Verdict::Accept(distances) } else { Verdict::Reject(distances, RejectReason::DetourTooLong) };
+
+        match *self {
+            DecisionRule::PerSide => per_side(1),
+            DecisionRule::Margin { margin } => per_side(margin as i64),
+            DecisionRule::Triangle { tolerance } => detour(to_upper + to_lower <= boundary + tolerance as i64),
+            DecisionRule::Ratio { max_percent } => detour(100 * (to_upper + to_lower) <= max_percent as i64 * boundary),
+        }
+    }
+}
+
+impl fmt::Display for DecisionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionRule::PerSide => write!(f, "per_side"),
+            DecisionRule::Triangle { tolerance } => write!(f, "triangle(+{})", tolerance),
+            DecisionRule::Ratio { max_percent } => write!(f, "ratio({}%)", max_percent),
+            DecisionRule::Margin { margin } => write!(f, "margin({})", margin),
+        }
+    }
+}
+
+/// How one rule did on a sweep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuleEvaluation {
+    pub rule: DecisionRule,
+    /// Outputs between the slippage boundaries, boundaries included.
+    pub inside: usize,
+    pub outside: usize,
+    /// Outside but accepted.
+    pub false_accepts: usize,
+    /// Inside but rejected.
+    pub false_rejects: usize,
+}
+
+impl RuleEvaluation {
+    pub fn false_accept_rate(&self) -> f64 {
+        self.false_accepts as f64 / self.outside.max(1) as f64
+    }
+
+    pub fn false_reject_rate(&self) -> f64 {
+        self.false_rejects as f64 / self.inside.max(1) as f64
+    }
+}
+
+impl fmt::Display for RuleEvaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: false accepts {}/{} ({:.4}) | false rejects {}/{} ({:.4})",
+            self.rule,
+            self.false_accepts,
+            self.outside,
+            self.false_accept_rate(),
+            self.false_rejects,
+            self.inside,
+            self.false_reject_rate()
+        )
+    }
+}
+
+/// Commits to `scenario` once per salt and judges every output of the sweep with every
+/// rule, each output hashed once per salt. `None` if the pool can't fill the trade.
+pub fn evaluate_rules(
+    scheme: &CommitmentScheme,
+    scenario: &Scenario,
+    slippage_bps: u64,
+    salts: &[Salt],
+    outputs: &[u64],
+    rules: &[DecisionRule],
+) -> Option<Vec<RuleEvaluation>> {
+    let output = scenario.pool.quote_exact_in(scenario.is_swap_x_to_y, scenario.input_amount)?;
+    let (upper_output, lower_output) = slippage_bounds(output, slippage_bps);
+
+    let mut evaluations: Vec<RuleEvaluation> =
+        rules.iter().map(|&rule| RuleEvaluation { rule, inside: 0, outside: 0, false_accepts: 0, false_rejects: 0 }).collect();
+
+    for salt in salts {
+        let commitment = scheme.commit(scenario, slippage_bps, salt)?;
+
+        for &executed_output in outputs {
+            let hash = scheme.hash(executed_output, salt);
+            let inside = (lower_output..=upper_output).contains(&executed_output);
+
+            for evaluation in &mut evaluations {
+                let accepted = commitment.verify_hash(evaluation.rule, &hash).is_accepted();
+
+                if inside {
+                    evaluation.inside += 1;
+                    evaluation.false_rejects += usize::from(!accepted);
+                } else {
+                    evaluation.outside += 1;
+                    evaluation.false_accepts += usize::from(accepted);
+                }
+            }
+        }
+    }
+
+    Some(evaluations)
+}
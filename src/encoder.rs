@@ -0,0 +1,563 @@
+//! Feature encoders, i.e. how a u64 output becomes the vector that gets projected.
+//! Each one is a port of an encoder from one of the experiment binaries.
+
+use std::f64::consts::PI;
+use std::fmt::{self, Debug};
+
+/// Encoded feature vector. Integer (fixed point) features are what the circuit can
+/// use directly, float features are only usable by the float hashers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Features {
+    Integer(Vec<u64>),
+    Float(Vec<f64>),
+}
+
+impl Features {
+    pub fn len(&self) -> usize {
+        match self {
+            Features::Integer(features) => features.len(),
+            Features::Float(features) => features.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn to_f64(&self) -> Vec<f64> {
+        match self {
+            Features::Integer(features) => features.iter().map(|&feature| feature as f64).collect(),
+            Features::Float(features) => features.clone(),
+        }
+    }
+}
+
+// prints the bare vector, like the binaries did before the encoders moved here
+impl fmt::Display for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Features::Integer(features) => write!(f, "{:?}", features),
+            Features::Float(features) => write!(f, "{:?}", features),
+        }
+    }
+}
+
+pub trait FeatureEncoder: Debug + Sync {
+    /// Name used by [`encoder_by_name`].
+    fn name(&self) -> &'static str;
+
+    /// Length of every vector returned by [`FeatureEncoder::encode`].
+    fn dimension(&self) -> usize;
+
+    fn encode(&self, value: u64) -> Features;
+}
+
+/// Looks up an encoder with the parameters the experiments used (64 bits, no cutoff).
+pub fn encoder_by_name(name: &str) -> Option<Box<dyn FeatureEncoder>> {
+    let encoder: Box<dyn FeatureEncoder> = match name {
+        "weighted_nibbles" => Box::new(WeightedNibbles { max_bits: 64, cut_off_index: 0 }),
+        "power_weighted_nibbles" => Box::new(PowerWeightedNibbles { max_bits: 64 }),
+        "averaged_weighted_nibbles" => Box::new(AveragedWeightedNibbles { max_bits: 64 }),
+        "centered_weighted_nibbles" => Box::new(CenteredWeightedNibbles { max_bits: 64 }),
+        "shifted_nibbles" => Box::new(ShiftedNibbles { max_bits: 64, cut_off_index: 0 }),
+        "nibbles" => Box::new(Nibbles { max_bits: 64, cut_off_index: 0 }),
+        "bits" => Box::new(Bits { max_bits: 64 }),
+        "bit_prefixes" => Box::new(BitPrefixes { max_bits: 64 }),
+        "gradual" => Box::new(Gradual),
+        "weighted_bit_pairs" => Box::new(WeightedBitPairs { max_bits: 64 }),
+        "weighted_bits" => Box::new(WeightedBits),
+        "bit_wma" => Box::new(BitWma),
+        "sequence" => Box::new(Sequence),
+        "checkpoints" => Box::new(Checkpoints),
+        "sine" => Box::new(Sine),
+        "normalized" => Box::new(Normalized),
+        _ => return None,
+    };
+
+    Some(encoder)
+}
+
+/// Names accepted by [`encoder_by_name`].
+pub const ENCODER_NAMES: [&str; 16] = [
+    "weighted_nibbles",
+    "power_weighted_nibbles",
+    "averaged_weighted_nibbles",
+    "centered_weighted_nibbles",
+    "shifted_nibbles",
+    "nibbles",
+    "bits",
+    "bit_prefixes",
+    "gradual",
+    "weighted_bit_pairs",
+    "weighted_bits",
+    "bit_wma",
+    "sequence",
+    "checkpoints",
+    "sine",
+    "normalized",
+];
+
+fn nibble_count(max_bits: u32) -> usize {
+    assert!(max_bits <= 64, "max_bits must be between 0 and 64");
+
+    max_bits.div_ceil(4) as usize
+}
+
+/// `split_u64_into_weighted_nibbles` (v4/v6): nibbles from the LSB, nibble `i` weighted
+/// by `i + 1 - cut_off_index`, nibbles below the cutoff are 0.
+#[derive(Clone, Copy, Debug)]
+pub struct WeightedNibbles {
+    pub max_bits: u32,
+    pub cut_off_index: u32,
+}
+
+impl FeatureEncoder for WeightedNibbles {
+    fn name(&self) -> &'static str {
+        "weighted_nibbles"
+    }
+
+    fn dimension(&self) -> usize {
+        nibble_count(self.max_bits)
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let features = (0..self.dimension() as u32)
+            .map(|i| {
+                if i < self.cut_off_index {
+                    return 0;
+                }
+
+                let nibble = (value >> (i * 4)) & 0xF;
+                let weight = ((i + 1) - self.cut_off_index) as u64; // Weight = i
+
+                nibble * weight
+            })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_into_weighted_nibbles` (v3): nibbles from the MSB, weighted 2^(n - i).
+#[derive(Clone, Copy, Debug)]
+pub struct PowerWeightedNibbles {
+    pub max_bits: u32,
+}
+
+impl FeatureEncoder for PowerWeightedNibbles {
+    fn name(&self) -> &'static str {
+        "power_weighted_nibbles"
+    }
+
+    fn dimension(&self) -> usize {
+        nibble_count(self.max_bits)
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let num_chunks = self.dimension() as u32;
+        let max_bits_rounded = num_chunks * 4;
+
+        let features = (0..num_chunks)
+            .map(|i| {
+                let nibble = (value >> (max_bits_rounded - (i + 1) * 4)) & 0xF;
+
+                nibble << (num_chunks - i)
+            })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// v4 multi boundary: [`WeightedNibbles`] averaged over every 3 neighbouring nibbles.
+#[derive(Clone, Copy, Debug)]
+pub struct AveragedWeightedNibbles {
+    pub max_bits: u32,
+}
+
+impl FeatureEncoder for AveragedWeightedNibbles {
+    fn name(&self) -> &'static str {
+        "averaged_weighted_nibbles"
+    }
+
+    fn dimension(&self) -> usize {
+        nibble_count(self.max_bits).saturating_sub(2)
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let Features::Integer(nibbles) = WeightedNibbles { max_bits: self.max_bits, cut_off_index: 0 }.encode(value) else {
+            unreachable!()
+        };
+
+        Features::Integer(nibbles.windows(3).map(|parts| parts.iter().sum::<u64>() / 3).collect())
+    }
+}
+
+/// v4 weight boundary norm: [`WeightedNibbles`] scaled by `(x / 15) * 2 - 1`.
+#[derive(Clone, Copy, Debug)]
+pub struct CenteredWeightedNibbles {
+    pub max_bits: u32,
+}
+
+impl FeatureEncoder for CenteredWeightedNibbles {
+    fn name(&self) -> &'static str {
+        "centered_weighted_nibbles"
+    }
+
+    fn dimension(&self) -> usize {
+        nibble_count(self.max_bits)
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let Features::Integer(nibbles) = WeightedNibbles { max_bits: self.max_bits, cut_off_index: 0 }.encode(value) else {
+            unreachable!()
+        };
+
+        Features::Float(nibbles.iter().map(|&nibble| (nibble as f64 / 15.0) * 2.0 - 1.0).collect())
+    }
+}
+
+/// `split_u64_into_shifted_nibbles` (v6 moving weight): a 4 bit window moved one bit
+/// at a time from the LSB, unweighted.
+#[derive(Clone, Copy, Debug)]
+pub struct ShiftedNibbles {
+    pub max_bits: u32,
+    pub cut_off_index: u32,
+}
+
+impl FeatureEncoder for ShiftedNibbles {
+    fn name(&self) -> &'static str {
+        "shifted_nibbles"
+    }
+
+    fn dimension(&self) -> usize {
+        assert!(self.max_bits <= 64 && self.max_bits > 3, "max_bits must be between 4 and 64");
+
+        (self.max_bits - 3) as usize
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let features = (0..self.dimension() as u32)
+            .map(|i| if i < self.cut_off_index { 0 } else { (value >> i) & 0xF })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_into_nibbles`: plain nibbles from the MSB.
+#[derive(Clone, Copy, Debug)]
+pub struct Nibbles {
+    pub max_bits: u32,
+    pub cut_off_index: u32,
+}
+
+impl FeatureEncoder for Nibbles {
+    fn name(&self) -> &'static str {
+        "nibbles"
+    }
+
+    fn dimension(&self) -> usize {
+        nibble_count(self.max_bits)
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let num_chunks = self.dimension() as u32;
+        let max_bits_rounded = num_chunks * 4;
+
+        let features = (0..num_chunks)
+            .map(|i| {
+                if i < self.cut_off_index {
+                    0
+                } else {
+                    (value >> (max_bits_rounded - (i + 1) * 4)) & 0xF
+                }
+            })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_with_max_bits` (v4 binary, v5): single bits from the MSB.
+#[derive(Clone, Copy, Debug)]
+pub struct Bits {
+    pub max_bits: u32,
+}
+
+impl FeatureEncoder for Bits {
+    fn name(&self) -> &'static str {
+        "bits"
+    }
+
+    fn dimension(&self) -> usize {
+        assert!(self.max_bits <= 64, "max_bits must be between 0 and 64");
+
+        self.max_bits as usize
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let features = (0..self.max_bits).map(|i| (value >> (self.max_bits - (i + 1))) & 1).collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_with_max_bits` (v4 weight boundary norm) and `split_u64_into_64`: entry
+/// `i` holds the top `i + 1` bits of the window, the last one one bit more.
+#[derive(Clone, Copy, Debug)]
+pub struct BitPrefixes {
+    pub max_bits: u32,
+}
+
+impl FeatureEncoder for BitPrefixes {
+    fn name(&self) -> &'static str {
+        "bit_prefixes"
+    }
+
+    fn dimension(&self) -> usize {
+        assert!(self.max_bits <= 64, "max_bits must be between 0 and 64");
+
+        self.max_bits as usize
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let mask = |bits: u32| if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 };
+
+        let features = (0..self.max_bits)
+            .map(|i| {
+                let shift = self.max_bits - (i + 1);
+                let bits = if i == self.max_bits - 1 { self.max_bits + 1 } else { i + 1 };
+
+                (value >> shift) & mask(bits)
+            })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_gradual`: segments of 1, 2, 4, 8, 16 and 33 bits from the MSB.
+#[derive(Clone, Copy, Debug)]
+pub struct Gradual;
+
+impl FeatureEncoder for Gradual {
+    fn name(&self) -> &'static str {
+        "gradual"
+    }
+
+    fn dimension(&self) -> usize {
+        6
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let bit_sizes = [1, 2, 4, 8, 16, 33];
+        let mut shift = 64;
+
+        let features = bit_sizes
+            .iter()
+            .map(|&size| {
+                shift -= size;
+                (value >> shift) & ((1 << size) - 1)
+            })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_into_weighted_bit_pairs`: 2 bit pairs from the MSB, weighted 2^(n - i).
+#[derive(Clone, Copy, Debug)]
+pub struct WeightedBitPairs {
+    pub max_bits: u32,
+}
+
+impl FeatureEncoder for WeightedBitPairs {
+    fn name(&self) -> &'static str {
+        "weighted_bit_pairs"
+    }
+
+    fn dimension(&self) -> usize {
+        assert!(self.max_bits <= 64, "max_bits must be between 0 and 64");
+
+        self.max_bits.div_ceil(2) as usize
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let num_chunks = self.dimension() as u32;
+        let max_bits_rounded = num_chunks * 2;
+
+        let features = (0..num_chunks)
+            .map(|i| {
+                let pair = (value >> (max_bits_rounded - (i + 1) * 2)) & 0x3;
+
+                pair << (num_chunks - i)
+            })
+            .collect();
+
+        Features::Integer(features)
+    }
+}
+
+/// `split_u64_into_weighted_bits`: bit `i` from the LSB weighted 2^i.
+#[derive(Clone, Copy, Debug)]
+pub struct WeightedBits;
+
+impl FeatureEncoder for WeightedBits {
+    fn name(&self) -> &'static str {
+        "weighted_bits"
+    }
+
+    fn dimension(&self) -> usize {
+        64
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        Features::Integer((0..64).map(|i| value & (1 << i)).collect())
+    }
+}
+
+/// v4 binary WMA: weighted moving average (weights 1, 2, 4, ... 128) over every
+/// window of 8 bits starting from the LSB, normalized to [0, 1].
+#[derive(Clone, Copy, Debug)]
+pub struct BitWma;
+
+impl FeatureEncoder for BitWma {
+    fn name(&self) -> &'static str {
+        "bit_wma"
+    }
+
+    fn dimension(&self) -> usize {
+        64 - 7
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let weights = [1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0];
+        let max_possible_sum = weights.iter().sum::<f64>();
+
+        let bits: Vec<f64> = (0..64).map(|i| ((value >> i) & 1) as f64).collect();
+
+        let features = bits
+            .windows(8)
+            .map(|window| window.iter().zip(weights.iter()).map(|(v, w)| v * w).sum::<f64>() / max_possible_sum)
+            .collect();
+
+        Features::Float(features)
+    }
+}
+
+const SEQUENCE_LENGTH: usize = 257;
+const SEQUENCE_HEIGHT: u32 = 16777216;
+
+/// v6 `generate_sequence`: the value is poured one unit at a time into 257 columns of
+/// height 2^24, filling them as a growing staircase, then normalized by the height.
+/// Only defined for 0 < value <= u32::MAX.
+#[derive(Clone, Copy, Debug)]
+pub struct Sequence;
+
+impl FeatureEncoder for Sequence {
+    fn name(&self) -> &'static str {
+        "sequence"
+    }
+
+    fn dimension(&self) -> usize {
+        SEQUENCE_LENGTH
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        assert!(value > 0 && value <= u32::MAX as u64, "sequence input must be between 1 and u32::MAX");
+
+        let mut sequence = [0u32; SEQUENCE_LENGTH];
+
+        let mut x = 0;
+        let mut count = 0;
+        let mut end = 0;
+        let mut start = 0;
+        loop {
+            sequence[x] += 1;
+            x += 1;
+
+            if x > end {
+                if end < SEQUENCE_LENGTH - 1 {
+                    end += 1;
+                }
+                x = start;
+            }
+
+            if x == start && sequence[start] == SEQUENCE_HEIGHT {
+                start += 1;
+                x = start;
+            }
+
+            count += 1;
+            if count >= value || start > end {
+                break;
+            }
+        }
+
+        Features::Float(sequence.iter().map(|&val| val as f64 / SEQUENCE_HEIGHT as f64).collect())
+    }
+}
+
+/// v6 checkpoints: `(2^i - 1 - value) / (2^i - 1)` clamped to [-1, 1] for i in 0..64.
+#[derive(Clone, Copy, Debug)]
+pub struct Checkpoints;
+
+impl FeatureEncoder for Checkpoints {
+    fn name(&self) -> &'static str {
+        "checkpoints"
+    }
+
+    fn dimension(&self) -> usize {
+        64
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        let features = (0..64u32)
+            .map(|i| {
+                let checkpoint = (2u64.pow(i) - 1) as i64;
+
+                // wraps like the release build of v6 for values above i64::MAX, the pinned vectors keep that
+                (checkpoint.wrapping_sub(value as i64) as f64 / checkpoint as f64).clamp(-1.0, 1.0)
+            })
+            .collect();
+
+        Features::Float(features)
+    }
+}
+
+/// v5 sine: 64 samples of a sine wave with the value as its frequency.
+#[derive(Clone, Copy, Debug)]
+pub struct Sine;
+
+impl FeatureEncoder for Sine {
+    fn name(&self) -> &'static str {
+        "sine"
+    }
+
+    fn dimension(&self) -> usize {
+        64
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        Features::Float((0..64).map(|i| (2.0 * PI * value as f64 * i as f64).sin()).collect())
+    }
+}
+
+/// v5 back to monke: the value itself scaled by 2^64 - 1.
+#[derive(Clone, Copy, Debug)]
+pub struct Normalized;
+
+impl FeatureEncoder for Normalized {
+    fn name(&self) -> &'static str {
+        "normalized"
+    }
+
+    fn dimension(&self) -> usize {
+        1
+    }
+
+    fn encode(&self, value: u64) -> Features {
+        Features::Float(vec![value as f64 / 18446744073709551615.0]) // 2^64 - 1
+    }
+}
@@ -0,0 +1,89 @@
+//! LSH evaluated the way the circom circuit does it, every projection, product and
+//! sum is a BN254 scalar field operation instead of `i128`/`BigInt`/float math.
+//!
+//! - the projection is the low 64 bits of the Poseidon output (`Num2Bits`), decoded
+//!   with [`Decoding::CIRCUIT`] (bit 63 is the sign, the remaining 63 bits the magnitude)
+//!   unless another decoding is asked for explicitly
+//! - a negative coefficient is `p - magnitude`
+//! - features enter as field elements, the dot product is summed mod p
+//! - the hash bit is circomlib's `Sign`: 1 if the sum is above `(p - 1) / 2`
+
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::hash::LshHash;
+use crate::lsh::LshConfig;
+use crate::matrix::ProjectionMatrix;
+use crate::projection::Decoding;
+
+pub use ark_bn254::Fr;
+
+/// Field element of a decoded coefficient as stored in [`ProjectionMatrix`].
+pub fn coefficient_to_field(coefficient: i64) -> Fr {
+    let magnitude = Fr::from(coefficient.unsigned_abs());
+
+    if coefficient < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// circomlib `Sign`, true if `value` is in the upper half of the field.
+pub fn is_negative(value: &Fr) -> bool {
+    value.into_bigint() > Fr::MODULUS_MINUS_ONE_DIV_TWO
+}
+
+/// Field element as the signed integer it stands for, upper half of the field negative.
+pub fn to_signed(value: &Fr) -> BigInt {
+    let unsigned = BigUint::from_bytes_le(&value.into_bigint().to_bytes_le());
+
+    if is_negative(value) {
+        let modulus = BigUint::from_bytes_le(&Fr::MODULUS.to_bytes_le());
+        BigInt::from_biguint(Sign::Minus, modulus - unsigned)
+    } else {
+        BigInt::from_biguint(Sign::Plus, unsigned)
+    }
+}
+
+/// Running sums of one projection row, entry `k` is the sum over the first `k + 1` features.
+pub fn field_partial_sums(features: &[u64], row: &[i64]) -> Vec<Fr> {
+    features
+        .iter()
+        .zip(row)
+        .scan(Fr::from(0u64), |sum, (&feature, &coefficient)| {
+            *sum += Fr::from(feature) * coefficient_to_field(coefficient);
+            Some(*sum)
+        })
+        .collect()
+}
+
+/// Field LSH with the cached [`ProjectionMatrix`] for `config`, decoded with
+/// [`Decoding::CIRCUIT`] whatever `config.decoding` says, so it matches the circuit.
+pub fn generate_lsh_field(features: &[u64], config: &LshConfig) -> LshHash {
+    generate_lsh_field_with(features, config, Decoding::CIRCUIT)
+}
+
+/// [`generate_lsh_field`] for a circuit generated with another `decoding`, it replaces
+/// `config.decoding`.
+pub fn generate_lsh_field_with(features: &[u64], config: &LshConfig, decoding: Decoding) -> LshHash {
+    lsh_field(features, &ProjectionMatrix::cached(&LshConfig { decoding, ..*config }, features.len() as u64))
+}
+
+pub(crate) fn lsh_field(features: &[u64], matrix: &ProjectionMatrix) -> LshHash {
+    assert_eq!(features.len() as u64, matrix.feature_count(), "feature count does not match the matrix");
+
+    let mut hash_res = LshHash::with_capacity(matrix.projection_count() as usize);
+
+    for row in matrix.rows() {
+        let mut final_sum = Fr::from(0u64);
+
+        for (&feature, &coefficient) in features.iter().zip(row) {
+            final_sum += Fr::from(feature) * coefficient_to_field(coefficient);
+        }
+
+        hash_res.push(is_negative(&final_sum));
+    }
+
+    hash_res
+}
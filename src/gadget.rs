@@ -0,0 +1,325 @@
+//! The LSH as an arkworks R1CS circuit, so proofs can be created and verified in process
+//! with Groth16 on BN254 instead of going through circom.
+//!
+//! [`LshCircuit`] proves that the hash of private integer features is within
+//! `max_distance` bits of a public reference hash:
+//!
+//! - features are private witnesses, range checked to 64 bits
+//! - coefficients are Poseidon(salt, i, j + offset) decoded with `config.decoding`, derived
+//!   in circuit for the [`Poseidon`](crate::Poseidon) source and constants for any other,
+//!   which fixes the salt at the setup
+//! - products and sums are field operations, same as [`generate_lsh_field`](crate::generate_lsh_field)
+//! - the bit is the sign of the sum. The sum is shifted by 2^200 and decomposed into 201
+//!   bits, exact because features and coefficients are below 2^64 (features up to 2^71)
+//! - public inputs are the salt commitment Poseidon(salt, blinding) (see [`Salt`]) and the
+//!   reference hash bits, salt and blinding stay private, the distance bound is a constant
+//!
+//! The sum is the exact integer sum, so the hash equals `generate_lsh_rust` for the same config.
+
+use ark_bn254::Bn254;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::uint64::UInt64;
+use ark_r1cs_std::{R1CSVar, ToBitsGadget};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use std::sync::Arc;
+
+use crate::field::{coefficient_to_field, Fr};
+use crate::hash::LshHash;
+use crate::lsh::LshConfig;
+use crate::matrix::ProjectionMatrix;
+use crate::projection::Decoding;
+use crate::salt::Salt;
+
+pub mod poseidon;
+
+/// Bits of the shifted projection sum, see the module docs.
+const SUM_BITS: usize = 201;
+
+/// Statement "the LSH of `features` is at most `max_distance` bits away from `reference`".
+///
+/// Only the [`Poseidon`](crate::Poseidon) source keeps the salt private. Any other source
+/// bakes the coefficients of the setup's salt into the circuit as constants, so the
+/// verifying key fixes the salt and a proof under any other salt fails.
+#[derive(Clone, Debug)]
+pub struct LshCircuit {
+    /// Salt and blinding are private, they open the public salt commitment.
+    pub config: LshConfig,
+    pub feature_count: usize,
+    /// Private input, `None` for the setup.
+    pub features: Option<Vec<u64>>,
+    /// Public input, `config.projection_count` bits.
+    pub reference: LshHash,
+    pub max_distance: u64,
+}
+
+impl LshCircuit {
+    /// Circuit with every input known, for proving. The salt of `config` is replaced by `salt`.
+    pub fn new(config: LshConfig, salt: &Salt, features: Vec<u64>, reference: LshHash, max_distance: u64) -> Self {
+        LshCircuit {
+            config: LshConfig { salt: *salt, ..config },
+            feature_count: features.len(),
+            features: Some(features),
+            reference,
+            max_distance,
+        }
+    }
+
+    /// Circuit shape only, for the setup.
+    pub fn blank(config: LshConfig, feature_count: usize, max_distance: u64) -> Self {
+        let reference = LshHash::zeros(config.projection_count as usize);
+
+        LshCircuit { config, feature_count, features: None, reference, max_distance }
+    }
+
+    /// Public inputs in allocation order: the salt commitment, then the reference bits.
+    pub fn public_inputs(&self) -> Vec<Fr> {
+        public_inputs(self.config.salt.commitment(), &self.reference)
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for LshCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        assert_eq!(self.reference.len() as u64, self.config.projection_count, "reference hash length does not match the config");
+
+        let salt = FpVar::new_witness(cs.clone(), || Ok(Fr::from(self.config.salt.value())))?;
+        let salt_blinding = FpVar::new_witness(cs.clone(), || Ok(self.config.salt.blinding()))?;
+        let commitment = FpVar::new_input(cs.clone(), || Ok(self.config.salt.commitment()))?;
+        poseidon::poseidon(&poseidon::parameters(2), &[salt.clone(), salt_blinding])?.enforce_equal(&commitment)?;
+
+        let reference = self.reference.iter().map(|bit| Boolean::new_input(cs.clone(), || Ok(bit))).collect::<Result<Vec<_>, _>>()?;
+
+        let features = (0..self.feature_count)
+            .map(|j| {
+                let feature = UInt64::new_witness(cs.clone(), || self.features.as_ref().map(|features| features[j]).ok_or(SynthesisError::AssignmentMissing))?;
+
+                Boolean::le_bits_to_fp_var(&feature.to_bits_le())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let coefficients = Coefficients::new(&self.config, self.feature_count);
+        let mut distance = FpVar::zero();
+
+        for (i, reference_bit) in reference.iter().enumerate() {
+            let mut sum = FpVar::zero();
+
+            for (j, feature) in features.iter().enumerate() {
+                sum += feature * coefficients.get(&salt, i as u64, j as u64)?;
+            }
+
+            let bit = sign_bit(cs.clone(), &sum)?;
+            distance += FpVar::from(bit.xor(reference_bit)?);
+        }
+
+        enforce_at_most(cs, &distance, self.max_distance, self.config.projection_count)
+    }
+}
+
+/// Coefficient variables, derived in circuit for Poseidon and constants otherwise.
+enum Coefficients<'a> {
+    Poseidon { config: &'a LshConfig, parameters: light_poseidon::PoseidonParameters<Fr> },
+    Constant(Arc<ProjectionMatrix>),
+}
+
+impl<'a> Coefficients<'a> {
+    fn new(config: &'a LshConfig, feature_count: usize) -> Self {
+        if config.source.name() == "poseidon" {
+            Coefficients::Poseidon { config, parameters: poseidon::parameters(3) }
+        } else {
+            Coefficients::Constant(ProjectionMatrix::cached(config, feature_count as u64))
+        }
+    }
+
+    fn get(&self, salt: &FpVar<Fr>, projection_index: u64, feature_index: u64) -> Result<FpVar<Fr>, SynthesisError> {
+        let (config, parameters) = match self {
+            Coefficients::Poseidon { config, parameters } => (config, parameters),
+            Coefficients::Constant(matrix) => return Ok(FpVar::constant(coefficient_to_field(matrix.get(projection_index, feature_index)))),
+        };
+
+        let input_index = config.input_index_offset + feature_index;
+        let hash = poseidon::poseidon(parameters, &[salt.clone(), FpVar::constant(Fr::from(projection_index)), FpVar::constant(Fr::from(input_index))])?;
+
+        // the projection is the low 64 bits of the hash
+        let bits = hash.to_bits_le()?;
+        let magnitude = Boolean::le_bits_to_fp_var(&bits[..63])?;
+        let msb = FpVar::from(bits[63].clone());
+
+        Ok(match config.decoding {
+            Decoding::TwosComplement => magnitude - msb * Fr::from(1u128 << 63),
+            Decoding::DropMsb => &magnitude - magnitude.clone() * msb.double()?,
+        })
+    }
+}
+
+/// 1 if `sum` is negative. `sum + 2^200` is decomposed into [`SUM_BITS`] bits, its top
+/// bit is 0 exactly for negative sums.
+fn sign_bit(cs: ConstraintSystemRef<Fr>, sum: &FpVar<Fr>) -> Result<Boolean<Fr>, SynthesisError> {
+    let shifted = sum + Fr::from(2u64).pow([SUM_BITS as u64 - 1]);
+    let value = shifted.value();
+
+    let bits = (0..SUM_BITS)
+        .map(|k| Boolean::new_witness(cs.clone(), || value.map(|value| value.into_bigint().get_bit(k))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&shifted)?;
+
+    Ok(bits[SUM_BITS - 1].not())
+}
+
+/// Enforces `distance <= max_distance` for a distance known to be in `0..=bound`, by
+/// decomposing `max_distance - distance` into as many bits as `bound` needs.
+fn enforce_at_most(cs: ConstraintSystemRef<Fr>, distance: &FpVar<Fr>, max_distance: u64, bound: u64) -> Result<(), SynthesisError> {
+    if max_distance >= bound {
+        return Ok(());
+    }
+
+    let slack = FpVar::constant(Fr::from(max_distance)) - distance;
+    let value = slack.value();
+    let bit_count = (u64::BITS - bound.leading_zeros()) as usize;
+
+    let bits = (0..bit_count)
+        .map(|k| Boolean::new_witness(cs.clone(), || value.map(|value| value.into_bigint().get_bit(k))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&slack)
+}
+
+/// Constraint and variable counts of the circuit for `config`, without building a witness.
+pub fn constraint_count(config: LshConfig, feature_count: usize) -> Result<(usize, usize), SynthesisError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+
+    LshCircuit::blank(config, feature_count, 0).generate_constraints(cs.clone())?;
+    cs.finalize();
+
+    Ok((cs.num_constraints(), cs.num_instance_variables() + cs.num_witness_variables()))
+}
+
+/// Whether `circuit` holds for its inputs, much faster than proving and verifying.
+pub fn is_satisfied(circuit: LshCircuit) -> Result<bool, SynthesisError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+
+    circuit.generate_constraints(cs.clone())?;
+    cs.is_satisfied()
+}
+
+/// Groth16 keys for `config`, `feature_count` and `max_distance`.
+pub fn setup<R: RngCore + CryptoRng>(
+    config: LshConfig,
+    feature_count: usize,
+    max_distance: u64,
+    rng: &mut R,
+) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), SynthesisError> {
+    Groth16::<Bn254>::circuit_specific_setup(LshCircuit::blank(config, feature_count, max_distance), rng)
+}
+
+pub fn prove<R: RngCore + CryptoRng>(proving_key: &ProvingKey<Bn254>, circuit: LshCircuit, rng: &mut R) -> Result<Proof<Bn254>, SynthesisError> {
+    Groth16::<Bn254>::prove(proving_key, circuit, rng)
+}
+
+/// Checks `proof` for the salt commitment and reference hash, see [`LshCircuit::public_inputs`].
+pub fn verify(verifying_key: &VerifyingKey<Bn254>, salt_commitment: Fr, reference: &LshHash, proof: &Proof<Bn254>) -> Result<bool, SynthesisError> {
+    Groth16::<Bn254>::verify(verifying_key, &public_inputs(salt_commitment, reference), proof)
+}
+
+fn public_inputs(salt_commitment: Fr, reference: &LshHash) -> Vec<Fr> {
+    std::iter::once(salt_commitment).chain(reference.iter().map(|bit| Fr::from(bit as u64))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::lsh::generate_lsh_rust;
+    use crate::projection::ChaCha8;
+    use crate::salt::salt_commitment;
+
+    const FEATURES: [u64; 2] = [1_000_000, 42];
+
+    fn salt() -> Salt {
+        Salt::new(7, Fr::from(11u64)).unwrap()
+    }
+
+    /// Constant ChaCha8 coefficients keep the tests fast, the salt is the same one the
+    /// proving circuit gets since constants fix it at the setup, see [`LshCircuit`].
+    fn config() -> LshConfig {
+        LshConfig { source: &ChaCha8, ..LshConfig::new(8, salt()) }
+    }
+
+    fn reference() -> LshHash {
+        generate_lsh_rust(&FEATURES, &config())
+    }
+
+    /// `reference` with its first `count` bits flipped.
+    fn flipped(count: usize) -> LshHash {
+        let mut hash = reference();
+        for index in 0..count {
+            hash.set(index, !hash.get(index));
+        }
+        hash
+    }
+
+    fn proof(max_distance: u64) -> (VerifyingKey<Bn254>, Proof<Bn254>) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (proving_key, verifying_key) = setup(config(), FEATURES.len(), max_distance, &mut rng).unwrap();
+        let circuit = LshCircuit::new(config(), &salt(), FEATURES.to_vec(), reference(), max_distance);
+
+        (verifying_key, prove(&proving_key, circuit, &mut rng).unwrap())
+    }
+
+    #[test]
+    fn proof_verifies() {
+        let (verifying_key, proof) = proof(0);
+
+        assert!(verify(&verifying_key, salt().commitment(), &reference(), &proof).unwrap());
+    }
+
+    /// In circuit Poseidon coefficients under a real proof, set up with another salt than
+    /// the one proven with: the salt is a witness, not part of the verifying key.
+    #[test]
+    fn poseidon_proof_verifies() {
+        let config = LshConfig { source: &crate::projection::Poseidon, ..config() };
+        let reference = generate_lsh_rust(&FEATURES, &config);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let setup_config = LshConfig { salt: Salt::new(1, Fr::from(2u64)).unwrap(), ..config };
+        let (proving_key, verifying_key) = setup(setup_config, FEATURES.len(), 0, &mut rng).unwrap();
+        let proof = prove(&proving_key, LshCircuit::new(config, &salt(), FEATURES.to_vec(), reference.clone(), 0), &mut rng).unwrap();
+
+        assert!(verify(&verifying_key, salt().commitment(), &reference, &proof).unwrap());
+    }
+
+    #[test]
+    fn poseidon_coefficients_are_satisfied() {
+        let config = LshConfig { source: &crate::projection::Poseidon, ..config() };
+        let reference = generate_lsh_rust(&FEATURES, &config);
+
+        assert!(is_satisfied(LshCircuit::new(config, &salt(), FEATURES.to_vec(), reference, 0)).unwrap());
+    }
+
+    #[test]
+    fn reference_beyond_max_distance_is_rejected() {
+        assert!(is_satisfied(LshCircuit::new(config(), &salt(), FEATURES.to_vec(), flipped(2), 2)).unwrap());
+        assert!(!is_satisfied(LshCircuit::new(config(), &salt(), FEATURES.to_vec(), flipped(3), 2)).unwrap());
+
+        let (verifying_key, proof) = proof(2);
+        assert!(!verify(&verifying_key, salt().commitment(), &flipped(3), &proof).unwrap());
+    }
+
+    #[test]
+    fn wrong_salt_commitment_is_rejected() {
+        let (verifying_key, proof) = proof(0);
+        let other_salt = Salt::new(8, Fr::from(11u64)).unwrap();
+
+        assert!(!verify(&verifying_key, other_salt.commitment(), &reference(), &proof).unwrap());
+        assert!(!verify(&verifying_key, salt_commitment(salt().value(), Fr::from(12u64)), &reference(), &proof).unwrap());
+    }
+}
@@ -0,0 +1,47 @@
+//! circomlib compatible Poseidon (BN254, x^5) as an R1CS gadget, the permutation
+//! `light_poseidon` (and so [`poseidon_projection`](crate::poseidon_projection)) computes.
+
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::SynthesisError;
+use light_poseidon::parameters::bn254_x5::get_poseidon_parameters;
+use light_poseidon::PoseidonParameters;
+
+use crate::field::Fr;
+
+/// Round constants and MDS matrix for `input_count` inputs (state width `input_count + 1`).
+pub fn parameters(input_count: usize) -> PoseidonParameters<Fr> {
+    get_poseidon_parameters::<Fr>(input_count as u8 + 1).expect("unsupported Poseidon width")
+}
+
+/// Poseidon hash of `inputs`, 3 constraints per S-box.
+pub fn poseidon(parameters: &PoseidonParameters<Fr>, inputs: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+    assert_eq!(inputs.len() + 1, parameters.width, "input count does not match the parameters");
+    assert_eq!(parameters.alpha, 5, "only the x^5 S-box is implemented");
+
+    // domain tag 0 first, like circomlib
+    let mut state: Vec<FpVar<Fr>> = std::iter::once(FpVar::zero()).chain(inputs.iter().cloned()).collect();
+
+    let half_full_rounds = parameters.full_rounds / 2;
+    let rounds = parameters.full_rounds + parameters.partial_rounds;
+
+    for round in 0..rounds {
+        for (i, element) in state.iter_mut().enumerate() {
+            *element += parameters.ark[round * parameters.width + i];
+        }
+
+        let is_full_round = round < half_full_rounds || round >= half_full_rounds + parameters.partial_rounds;
+        let sbox_count = if is_full_round { parameters.width } else { 1 };
+
+        for element in state.iter_mut().take(sbox_count) {
+            let square = element.square()?;
+            *element = square.square()? * &*element;
+        }
+
+        state = (0..parameters.width)
+            .map(|i| state.iter().zip(&parameters.mds[i]).fold(FpVar::zero(), |sum, (element, &mds)| sum + element * mds))
+            .collect();
+    }
+
+    Ok(state.swap_remove(0))
+}
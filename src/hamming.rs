@@ -0,0 +1,18 @@
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn hamming_distance_128(a: u128, b: u128) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Compares two '0'/'1' hash strings char by char, panics if the lengths differ.
+/// Prefer [`LshHash::hamming_distance`](crate::LshHash::hamming_distance).
+pub fn hamming_distance_string(a: &str, b: &str) -> u32 {
+    assert_eq!(a.len(), b.len(), "hash length mismatch");
+
+    a.chars()
+        .zip(b.chars())
+        .filter(|(char_a, char_b)| char_a != char_b)
+        .count() as u32
+}
@@ -0,0 +1,181 @@
+//! Packed LSH hash. Bits are stored MSB first in `u64` words, so bit `i` of a word is
+//! `1 << (63 - i)`, the same order `main_64`/`main_128` used for their `u64`/`u128`
+//! hashes and the same order the '0'/'1' strings are printed in.
+
+use std::fmt;
+use std::ops::BitXor;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LshHash {
+    words: Vec<u64>,
+    len: usize,
+}
+
+/// Error from parsing a '0'/'1' string into an [`LshHash`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseHashError {
+    pub index: usize,
+    pub found: char,
+}
+
+impl fmt::Display for ParseHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid hash bit '{}' at index {}", self.found, self.index)
+    }
+}
+
+impl std::error::Error for ParseHashError {}
+
+// bit `index` inside its word, MSB first
+fn bit_mask(index: usize) -> u64 {
+    1 << (63 - index % 64)
+}
+
+impl LshHash {
+    /// All zero hash of `len` bits.
+    pub fn zeros(len: usize) -> Self {
+        LshHash { words: vec![0; len.div_ceil(64)], len }
+    }
+
+    pub fn with_capacity(bits: usize) -> Self {
+        LshHash { words: Vec::with_capacity(bits.div_ceil(64)), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Packed words, unused low bits of the last word are always 0.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, bit);
+    }
+
+    /// Appends the bits of `other`, e.g. to concatenate two half hashes.
+    pub fn append(&mut self, other: &LshHash) {
+        for bit in other.iter() {
+            self.push(bit);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "bit index {} out of range for {} bit hash", index, self.len);
+
+        self.words[index / 64] & bit_mask(index) != 0
+    }
+
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len, "bit index {} out of range for {} bit hash", index, self.len);
+
+        if bit {
+            self.words[index / 64] |= bit_mask(index);
+        } else {
+            self.words[index / 64] &= !bit_mask(index);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /// Number of differing bits, panics if the lengths differ.
+    pub fn hamming_distance(&self, other: &LshHash) -> u32 {
+        self.checked_hamming_distance(other)
+            .unwrap_or_else(|| panic!("hash length mismatch: {} vs {}", self.len, other.len))
+    }
+
+    /// Number of differing bits, `None` if the lengths differ.
+    pub fn checked_hamming_distance(&self, other: &LshHash) -> Option<u32> {
+        if self.len != other.len {
+            return None;
+        }
+
+        Some(self.words.iter().zip(other.words.iter()).map(|(a, b)| (a ^ b).count_ones()).sum())
+    }
+
+    /// The hash as the `u64` of `main_64`, only for 64 bit hashes.
+    pub fn to_u64(&self) -> Option<u64> {
+        (self.len == 64).then(|| self.words[0])
+    }
+
+    /// The hash as the `u128` of `main_128`, only for 128 bit hashes.
+    pub fn to_u128(&self) -> Option<u128> {
+        (self.len == 128).then(|| ((self.words[0] as u128) << 64) | self.words[1] as u128)
+    }
+}
+
+impl BitXor for &LshHash {
+    type Output = LshHash;
+
+    /// Panics if the lengths differ.
+    fn bitxor(self, other: &LshHash) -> LshHash {
+        assert_eq!(self.len, other.len, "hash length mismatch");
+
+        LshHash { words: self.words.iter().zip(other.words.iter()).map(|(a, b)| a ^ b).collect(), len: self.len }
+    }
+}
+
+impl From<u64> for LshHash {
+    fn from(value: u64) -> Self {
+        LshHash { words: vec![value], len: 64 }
+    }
+}
+
+impl From<u128> for LshHash {
+    fn from(value: u128) -> Self {
+        LshHash { words: vec![(value >> 64) as u64, value as u64], len: 128 }
+    }
+}
+
+impl FromIterator<bool> for LshHash {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut hash = LshHash::default();
+        for bit in iter {
+            hash.push(bit);
+        }
+
+        hash
+    }
+}
+
+impl FromStr for LshHash {
+    type Err = ParseHashError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .enumerate()
+            .map(|(index, found)| match found {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(ParseHashError { index, found }),
+            })
+            .collect()
+    }
+}
+
+/// Same '0'/'1' string the hashers used to return.
+impl fmt::Display for LshHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! Commitment inversion: an observer who knows (or guesses) the salt can hash candidate
+//! outputs and compare them with a committed hash. Inverting the upper and lower hashes
+//! of a [`SlippageCommitment`] gives the slippage window, and how wide the matching
+//! intervals are is how much privacy is left.
+//!
+//! [`invert_hash`] searches a value range. Where the hash is local (the distance to the
+//! target shrinks as the value nears it) it does not need to enumerate the range:
+//!
+//! - sample the range evenly, keep the `beam` samples closest to the target hash
+//! - zoom into the neighbourhood of each of them (overlapping neighbourhoods merged) and
+//!   sample again, until every window is sampled at step 1
+//! - grow every exact enough match into the interval of values around it that match
+//!
+//! Matches far from the beam's samples are missed. The nibble encoders are far from
+//! local (one unit more flips a few percent of the bits), for them the range has to be
+//! narrow enough to sample every value, which the pool state usually gives the observer.
+//! [`invert_slippage`] is the observer that also sees the input amount, so it knows the
+//! quoted output and only enumerates the slippage.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::amm::slippage_bounds;
+use crate::commitment::SlippageCommitment;
+use crate::hash::LshHash;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InversionSearch {
+    /// Values the observer considers, e.g. everything the pool could pay out.
+    pub range: RangeInclusive<u64>,
+    /// Samples per window and level, at least 4. At least the width of `range` samples
+    /// every value.
+    pub samples: u64,
+    /// Windows refined per level.
+    pub beam: usize,
+    /// Largest Hamming distance that counts as a match, 0 for the exact hash.
+    pub max_distance: u32,
+}
+
+/// Result of [`invert_hash`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inversion {
+    /// Disjoint matching intervals in ascending order.
+    pub intervals: Vec<RangeInclusive<u64>>,
+    /// Smallest distance to the target seen.
+    pub best_distance: u32,
+    pub evaluations: usize,
+}
+
+impl Inversion {
+    /// Number of values the target could be.
+    pub fn candidates(&self) -> u64 {
+        self.intervals.iter().map(|interval| interval.end() - interval.start() + 1).sum()
+    }
+
+    /// Smallest interval holding every candidate, `None` without a match.
+    pub fn hull(&self) -> Option<RangeInclusive<u64>> {
+        Some(*self.intervals.first()?.start()..=*self.intervals.last()?.end())
+    }
+}
+
+/// Candidate values hashing within `search.max_distance` of `target`, see the module docs.
+pub fn invert_hash<F: Fn(u64) -> LshHash>(target: &LshHash, search: &InversionSearch, lsh: F) -> Inversion {
+    let mut evaluations = 0;
+    let mut distance = |value: u64| {
+        evaluations += 1;
+        lsh(value).hamming_distance(target)
+    };
+
+    let mut matches = Vec::new();
+    let mut best_distance = u32::MAX;
+    let samples = search.samples.max(4);
+    let window_step = |window: &RangeInclusive<u64>| ((window.end() - window.start()) / samples).max(1);
+    // every window with the step it is sampled at
+    let mut windows = vec![(search.range.clone(), window_step(&search.range))];
+
+    loop {
+        let mut scored = Vec::new();
+
+        for (window, step) in &windows {
+            for value in (*window.start()..=*window.end()).step_by(*step as usize).chain([*window.end()]) {
+                let value_distance = distance(value);
+
+                best_distance = best_distance.min(value_distance);
+                if value_distance <= search.max_distance {
+                    matches.push(value);
+                }
+                scored.push((value_distance, value, *step));
+            }
+        }
+
+        // done once every window is sampled at step 1, not just the last one
+        if windows.iter().all(|&(_, step)| step == 1) {
+            break;
+        }
+
+        scored.sort_unstable();
+        scored.dedup_by_key(|(_, value, _)| *value);
+
+        let mut next: Vec<(RangeInclusive<u64>, u64)> = scored
+            .into_iter()
+            .take(search.beam.max(1))
+            .map(|(_, value, step)| {
+                let window = value.saturating_sub(step).max(*search.range.start())..=value.saturating_add(step).min(*search.range.end());
+                let step = window_step(&window);
+                (window, step)
+            })
+            .collect();
+        next.sort_unstable_by_key(|(window, _)| *window.start());
+
+        // overlapping windows would hash the same values again, a merged window keeps the
+        // coarser step so the steps still shrink every level
+        windows = Vec::with_capacity(next.len());
+        for (window, step) in next {
+            match windows.last_mut() {
+                Some((last, last_step)) if *window.start() <= last.end().saturating_add(1) => {
+                    *last = *last.start()..=(*window.end()).max(*last.end());
+                    *last_step = step.max(*last_step);
+                }
+                _ => windows.push((window, step)),
+            }
+        }
+    }
+
+    matches.sort_unstable();
+    matches.dedup();
+
+    let mut is_match = |value: u64| distance(value) <= search.max_distance;
+    let mut intervals: Vec<RangeInclusive<u64>> = Vec::new();
+
+    for value in matches {
+        if intervals.last().is_some_and(|interval| interval.contains(&value)) {
+            continue;
+        }
+
+        let start = edge(value, *search.range.start(), &mut is_match);
+        let end = edge(value, *search.range.end(), &mut is_match);
+
+        match intervals.last_mut() {
+            Some(last) if last.end().saturating_add(1) >= start => *last = *last.start()..=end.max(*last.end()),
+            _ => intervals.push(start..=end),
+        }
+    }
+
+    Inversion { intervals, best_distance, evaluations }
+}
+
+/// Last matching value walking from the matching `value` toward `limit`, galloping to a
+/// non-match and bisecting back. Assumes the matches in between are contiguous.
+fn edge<M: FnMut(u64) -> bool>(value: u64, limit: u64, is_match: &mut M) -> u64 {
+    let toward = |value: u64, step: u64| if limit < value { value.saturating_sub(step).max(limit) } else { value.saturating_add(step).min(limit) };
+
+    let mut matching = value;
+    let mut step = 1;
+    let not_matching = loop {
+        let next = toward(matching, step);
+
+        if next == matching {
+            return matching;
+        }
+        if !is_match(next) {
+            break next;
+        }
+        matching = next;
+        step = step.saturating_mul(2);
+    };
+
+    let upward = matching < not_matching;
+    let (mut low, mut high) = (matching.min(not_matching), matching.max(not_matching));
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+
+        if is_match(middle) == upward {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    if upward {
+        low
+    } else {
+        high
+    }
+}
+
+/// Both boundaries of a commitment inverted under one salt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitmentInversion {
+    pub upper: Inversion,
+    pub lower: Inversion,
+}
+
+impl CommitmentInversion {
+    /// Whether both hashes matched, with the lower boundary below the upper one. A wrong
+    /// salt rarely gets there.
+    pub fn is_consistent(&self) -> bool {
+        match (self.lower.hull(), self.upper.hull()) {
+            (Some(lower), Some(upper)) => lower.start() <= upper.end(),
+            _ => false,
+        }
+    }
+
+    /// Slippage window the observer recovers: from the lowest lower candidate to the highest upper one.
+    pub fn window(&self) -> Option<RangeInclusive<u64>> {
+        Some(*self.lower.hull()?.start()..=*self.upper.hull()?.end())
+    }
+
+    /// Quoted output implied by the middle of both hulls, with the slippage in bps it
+    /// implies. See [`slippage_bounds`](crate::amm::slippage_bounds).
+    pub fn estimate(&self) -> Option<(u64, u64)> {
+        let (lower, upper) = (self.lower.hull()?, self.upper.hull()?);
+        let lower = (*lower.start() as u128 + *lower.end() as u128) / 2;
+        let upper = (*upper.start() as u128 + *upper.end() as u128) / 2;
+
+        let output = (lower + upper) / 2;
+        let slippage = (upper.saturating_sub(lower) * 10_000 / (2 * output.max(1))) as u64;
+
+        Some((output as u64, slippage))
+    }
+}
+
+impl fmt::Display for CommitmentInversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, inversion) in [("upper", &self.upper), ("lower", &self.lower)] {
+            let hull = inversion.hull().map_or("none".to_string(), |hull| format!("{}..={}", hull.start(), hull.end()));
+
+            writeln!(
+                f,
+                "{}: {} candidates in {} interval(s) | hull {} | best distance {} | evaluations {}",
+                name,
+                inversion.candidates(),
+                inversion.intervals.len(),
+                hull,
+                inversion.best_distance,
+                inversion.evaluations
+            )?;
+        }
+
+        match self.estimate() {
+            Some((output, slippage)) => writeln!(f, "estimated output {} | estimated slippage {} bps", output, slippage),
+            None => writeln!(f, "estimated output: none"),
+        }
+    }
+}
+
+/// Inverts the upper and lower hashes of `commitment` with `lsh`, the hash under the
+/// salt being tried.
+pub fn invert_commitment<F: Fn(u64) -> LshHash>(commitment: &SlippageCommitment, search: &InversionSearch, lsh: F) -> CommitmentInversion {
+    CommitmentInversion { upper: invert_hash(&commitment.upper_hash, search, &lsh), lower: invert_hash(&commitment.lower_hash, search, &lsh) }
+}
+
+/// Slippages in `0..=max_slippage` bps whose boundaries for the quoted `output` hash
+/// within `max_distance` of the committed ones: what an observer who can quote the trade
+/// learns. A single match is the user's exact slippage.
+pub fn invert_slippage<F: Fn(u64) -> LshHash>(commitment: &SlippageCommitment, output: u64, max_slippage: u64, max_distance: u32, lsh: F) -> Vec<u64> {
+    (0..=max_slippage)
+        .filter(|&slippage| {
+            let (upper_output, lower_output) = slippage_bounds(output, slippage);
+
+            lsh(upper_output).hamming_distance(&commitment.upper_hash) <= max_distance
+                && lsh(lower_output).hamming_distance(&commitment.lower_hash) <= max_distance
+        })
+        .collect()
+}
@@ -0,0 +1,248 @@
+//! How much the hashes leak statistically, without inverting them: estimates of the
+//! mutual information between the hash bits and the output amount, and between the
+//! boundary hashes and the slippage, over trades drawn from a [`TradePrior`].
+//!
+//! The output is binned into `bins` equally likely bins, so it carries about
+//! `log2(bins)` bits and every estimate is in bits, out of that. For the output:
+//!
+//! - per bit, I(bit; bin): their sum ignores the dependencies between bits, their
+//!   maximum shows whether single bits give the output away
+//! - the plug-in I(hash; bin) over whole hashes. The hash is a function of the output,
+//!   so with as many distinct hashes as samples it reads as full leakage, an upper
+//!   estimate that needs many samples per hash to mean more
+//! - a Fano lower bound from how often the nearest (Hamming) hash of a held out half of
+//!   the samples lands in the right bin, what a simple observer provably learns
+//!
+//! For the slippage the observer sees both boundary hashes, the estimates are I(boundary
+//! distance; slippage), from the Hamming distance alone, and the Fano bound of the
+//! nearest neighbour over both hashes.
+//!
+//! Entropies are plug-in estimates with the Miller-Madow correction. Samples cycle
+//! through the given salts: one salt is an observer that knows it (or sees many orders
+//! under it), fresh salts per order are closer to one that doesn't.
+
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+use crate::amm::{slippage_bounds, Pool};
+use crate::commitment::CommitmentScheme;
+use crate::hash::LshHash;
+use crate::salt::Salt;
+
+/// The trades an observer expects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TradePrior {
+    pub is_swap_x_to_y: bool,
+    /// Input amounts, drawn log-uniformly, trade sizes span orders of magnitude.
+    pub input_range: RangeInclusive<u64>,
+    /// Slippages in bps, drawn uniformly.
+    pub slippages: Vec<u64>,
+}
+
+/// One trade drawn from a [`TradePrior`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Trade {
+    pub input_amount: u64,
+    pub output: u64,
+    pub slippage: u64,
+}
+
+impl TradePrior {
+    /// A trade quoted by `pool`, `None` if the pool can't fill it, it has no output or a
+    /// slippage boundary is 0.
+    pub fn sample<R: Rng>(&self, pool: &dyn Pool, rng: &mut R) -> Option<Trade> {
+        let (low, high) = ((*self.input_range.start()).max(1) as f64, (*self.input_range.end()).max(1) as f64);
+        let input_amount = (low.ln() + rng.random::<f64>() * (high / low).ln()).exp().round() as u64;
+        let slippage = self.slippages[rng.random_range(0..self.slippages.len())];
+
+        let output = pool.quote_exact_in(self.is_swap_x_to_y, input_amount)?;
+        (slippage_bounds(output, slippage).1 > 0).then_some(Trade { input_amount, output, slippage })
+    }
+}
+
+/// Result of [`estimate_leakage`], in bits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeakageEstimate {
+    pub samples: usize,
+    pub bins: usize,
+    pub output_entropy: f64,
+    /// I(bit; output bin) for every bit of the output's hash.
+    pub bit_leakage: Vec<f64>,
+    /// Plug-in I(hash; output bin).
+    pub hash_leakage: f64,
+    pub distinct_hashes: usize,
+    /// Fano lower bound of the nearest neighbour decoder of the output bin.
+    pub output_decoding_bound: f64,
+    pub slippage_entropy: f64,
+    /// I(boundary distance; slippage).
+    pub boundary_distance_leakage: f64,
+    /// Fano lower bound of the nearest neighbour decoder of the slippage.
+    pub slippage_decoding_bound: f64,
+}
+
+impl LeakageEstimate {
+    pub fn bit_leakage_sum(&self) -> f64 {
+        self.bit_leakage.iter().sum()
+    }
+
+    pub fn max_bit_leakage(&self) -> f64 {
+        self.bit_leakage.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+impl fmt::Display for LeakageEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "output: entropy {:.3} ({} bins, {} samples) | bits sum {:.3} max {:.4} | hash {:.3} ({} distinct) | decoding bound {:.3}",
+            self.output_entropy,
+            self.bins,
+            self.samples,
+            self.bit_leakage_sum(),
+            self.max_bit_leakage(),
+            self.hash_leakage,
+            self.distinct_hashes,
+            self.output_decoding_bound
+        )?;
+        write!(
+            f,
+            "slippage: entropy {:.3} | boundary distance {:.3} | decoding bound {:.3}",
+            self.slippage_entropy, self.boundary_distance_leakage, self.slippage_decoding_bound
+        )
+    }
+}
+
+/// Estimates the leakage of `scheme` over `samples` trades drawn from `prior` with `rng`,
+/// see the module docs. Trades the pool can't fill are redrawn, `None` if no trade
+/// could be drawn in `samples` tries, `salts` is empty, `bins` is below 2 or there are
+/// fewer `samples` than `bins`, which could not fill every bin.
+pub fn estimate_leakage<R: Rng>(
+    scheme: &CommitmentScheme,
+    pool: &dyn Pool,
+    prior: &TradePrior,
+    salts: &[Salt],
+    samples: usize,
+    bins: usize,
+    rng: &mut R,
+) -> Option<LeakageEstimate> {
+    if salts.is_empty() || bins < 2 || samples < bins || prior.slippages.is_empty() {
+        return None;
+    }
+
+    let mut trades = Vec::with_capacity(samples);
+    let mut failures = 0;
+    while trades.len() < samples {
+        match prior.sample(pool, rng) {
+            Some(trade) => trades.push(trade),
+            None if failures < samples => failures += 1,
+            None => return None,
+        }
+    }
+
+    let mut outputs: Vec<u64> = trades.iter().map(|trade| trade.output).collect();
+    outputs.sort_unstable();
+    let edges: Vec<u64> = (1..bins).map(|bin| outputs[bin * samples / bins]).collect();
+    let output_bins: Vec<usize> = trades.iter().map(|trade| edges.partition_point(|&edge| edge <= trade.output)).collect();
+
+    let mut slippage_classes = prior.slippages.clone();
+    slippage_classes.sort_unstable();
+    slippage_classes.dedup();
+    let slippages: Vec<usize> = trades.iter().map(|trade| slippage_classes.binary_search(&trade.slippage).unwrap()).collect();
+
+    let hashes: Vec<(LshHash, LshHash, LshHash)> = trades
+        .iter()
+        .zip(salts.iter().cycle())
+        .map(|(trade, salt)| {
+            let (upper_output, lower_output) = slippage_bounds(trade.output, trade.slippage);
+            (scheme.hash(trade.output, salt), scheme.hash(upper_output, salt), scheme.hash(lower_output, salt))
+        })
+        .collect();
+
+    let bit_count = hashes.first().map_or(0, |(hash, ..)| hash.len());
+    let bit_leakage = (0..bit_count)
+        .map(|bit| mutual_information(hashes.iter().zip(&output_bins).map(|((hash, ..), &bin)| (hash.get(bit), bin))))
+        .collect();
+
+    let output_entropy = entropy(output_bins.iter().copied());
+    let output_accuracy = nearest_neighbour_accuracy(&hashes, &output_bins, |a, b| a.0.hamming_distance(&b.0));
+    let slippage_entropy = entropy(slippages.iter().copied());
+    let slippage_accuracy =
+        nearest_neighbour_accuracy(&hashes, &slippages, |a, b| a.1.hamming_distance(&b.1) + a.2.hamming_distance(&b.2));
+
+    Some(LeakageEstimate {
+        samples,
+        bins,
+        output_entropy,
+        bit_leakage,
+        hash_leakage: mutual_information(hashes.iter().zip(&output_bins).map(|((hash, ..), &bin)| (hash, bin))),
+        distinct_hashes: hashes.iter().map(|(hash, ..)| hash).collect::<HashSet<_>>().len(),
+        output_decoding_bound: fano_bound(output_entropy, output_accuracy, bins),
+        slippage_entropy,
+        boundary_distance_leakage: mutual_information(
+            hashes.iter().zip(&slippages).map(|((_, upper, lower), &slippage)| (upper.hamming_distance(lower), slippage)),
+        ),
+        slippage_decoding_bound: fano_bound(slippage_entropy, slippage_accuracy, slippage_classes.len()),
+    })
+}
+
+/// Miller-Madow corrected plug-in entropy of the samples, in bits.
+fn entropy<T: Hash + Eq>(samples: impl IntoIterator<Item = T>) -> f64 {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for sample in samples {
+        *counts.entry(sample).or_default() += 1;
+    }
+
+    let total = counts.values().sum::<usize>() as f64;
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    let plug_in: f64 = counts.values().map(|&count| count as f64 / total).map(|p| -p * p.log2()).sum();
+
+    plug_in + (counts.len() - 1) as f64 / (2.0 * total * std::f64::consts::LN_2)
+}
+
+/// H(X) + H(Y) - H(X, Y) of the pairs, at least 0.
+fn mutual_information<X: Hash + Eq + Clone, Y: Hash + Eq + Clone>(pairs: impl IntoIterator<Item = (X, Y)>) -> f64 {
+    let pairs: Vec<(X, Y)> = pairs.into_iter().collect();
+
+    let joint = entropy(pairs.iter().cloned());
+    let x = entropy(pairs.iter().map(|(x, _)| x.clone()));
+    let y = entropy(pairs.iter().map(|(_, y)| y.clone()));
+
+    (x + y - joint).max(0.0)
+}
+
+/// Share of the second half of `samples` whose nearest neighbour in the first half, by
+/// `distance`, has the same label.
+fn nearest_neighbour_accuracy<T, D: Fn(&T, &T) -> u32>(samples: &[T], labels: &[usize], distance: D) -> f64 {
+    let split = samples.len() / 2;
+    let (train, test) = samples.split_at(split);
+
+    if train.is_empty() || test.is_empty() {
+        return 0.0;
+    }
+
+    let correct = test
+        .iter()
+        .zip(&labels[split..])
+        .filter(|(sample, &label)| {
+            let nearest = (0..split).min_by_key(|&index| distance(&train[index], sample)).unwrap();
+            labels[nearest] == label
+        })
+        .count();
+
+    correct as f64 / test.len() as f64
+}
+
+/// Fano's inequality: a decoder right with probability `accuracy` over `classes` classes
+/// of entropy `entropy` learns at least `entropy - h(error) - error * log2(classes - 1)`.
+fn fano_bound(entropy: f64, accuracy: f64, classes: usize) -> f64 {
+    let error = 1.0 - accuracy;
+    let binary_entropy = |p: f64| if p <= 0.0 || p >= 1.0 { 0.0 } else { -p * p.log2() - (1.0 - p) * (1.0 - p).log2() };
+
+    (entropy - binary_entropy(error) - error * ((classes.max(2) - 1) as f64).log2()).max(0.0)
+}
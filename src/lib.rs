@@ -0,0 +1,36 @@
+//! Shared LSH, projection and AMM helpers used by the experiment binaries.
+//!
+//! Every `v*`/`main_*` binary used to carry its own copy of these functions. They
+//! live here now so a fix to the projection or AMM math lands everywhere at once.
+
+pub mod amm;
+pub mod attack;
+pub mod boundary;
+pub mod circom;
+pub mod commitment;
+pub mod encoder;
+pub mod field;
+pub mod gadget;
+pub mod hamming;
+pub mod hash;
+pub mod inversion;
+pub mod leakage;
+pub mod lsh;
+pub mod matrix;
+pub mod projection;
+pub mod salt;
+pub mod sandwich;
+pub mod threshold;
+pub mod vectors;
+
+pub use amm::{fake_trade_to_x, fake_trade_to_y, pool_by_name, slippage_bounds, swap, Pool};
+pub use boundary::{get_boundary_hashes, get_hash, get_pool_boundary_hashes, get_pool_hash};
+pub use encoder::{encoder_by_name, FeatureEncoder, Features};
+pub use field::{generate_lsh_field, generate_lsh_field_with};
+pub use hamming::{hamming_distance, hamming_distance_128, hamming_distance_string};
+pub use hash::LshHash;
+pub use lsh::{generate_lsh, generate_lsh_circuit, generate_lsh_rust, generate_lsh_rust_f64, hash_value, LshConfig};
+pub use matrix::ProjectionMatrix;
+pub use projection::equivalence::{check_equivalence, EquivalenceReport};
+pub use projection::divergence::{decoding_divergence, DivergenceReport};
+pub use projection::{poseidon_projection, ChaCha8, Decoding, Poseidon, ProjectionSource};
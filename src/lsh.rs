@@ -0,0 +1,150 @@
+use num_bigint::BigInt;
+
+use crate::encoder::{FeatureEncoder, Features};
+use crate::hash::LshHash;
+use crate::matrix::ProjectionMatrix;
+use crate::projection::{Decoding, Poseidon, ProjectionSource};
+use crate::salt::Salt;
+
+/// Settings shared by every LSH variant.
+#[derive(Clone, Copy, Debug)]
+pub struct LshConfig {
+    /// Only its value enters the projections. A [`Salt`] so salt 0 takes [`Salt::test`].
+    pub salt: Salt,
+    /// Number of projections, one hash bit each (generally recommended 512/1024 for
+    /// higher precision but lower performance).
+    pub projection_count: u64,
+    /// Input index of the first feature. Older versions start at 1000 or 5000.
+    pub input_index_offset: u64,
+    /// Where the projection coefficients come from, [`Poseidon`] matches the circuit.
+    pub source: &'static dyn ProjectionSource,
+    /// How raw projection bits become signed coefficients, for every hasher.
+    pub decoding: Decoding,
+}
+
+impl LshConfig {
+    pub fn new(projection_count: u64, salt: Salt) -> Self {
+        LshConfig { salt, projection_count, input_index_offset: 0, source: &Poseidon, decoding: Decoding::TwosComplement }
+    }
+}
+
+/// Hashes encoded features with [`generate_lsh_rust`] or [`generate_lsh_rust_f64`].
+pub fn generate_lsh(features: &Features, config: &LshConfig) -> LshHash {
+    match features {
+        Features::Integer(features) => generate_lsh_rust(features, config),
+        Features::Float(features) => generate_lsh_rust_f64(features, config),
+    }
+}
+
+/// Encodes `value` and hashes it, see [`generate_lsh`].
+pub fn hash_value(encoder: &dyn FeatureEncoder, value: u64, config: &LshConfig) -> LshHash {
+    generate_lsh(&encoder.encode(value), config)
+}
+
+/// Integer LSH: each feature is multiplied by its i64 projection and summed without
+/// overflow, a negative sum sets the bit. Bits are ordered MSB first, see [`LshHash`].
+/// Projections come from the cached [`ProjectionMatrix`] for `config`.
+pub fn generate_lsh_rust(features: &[u64], config: &LshConfig) -> LshHash {
+    lsh_rust(features, &ProjectionMatrix::cached(config, features.len() as u64))
+}
+
+/// Float LSH over normalized features, projections are scaled to [-1, 1] by 2^63 - 1.
+pub fn generate_lsh_rust_f64(features: &[f64], config: &LshConfig) -> LshHash {
+    lsh_rust_f64(features, &ProjectionMatrix::cached(config, features.len() as u64))
+}
+
+/// Same hash as [`generate_lsh_rust`] but written to mimic the zk circuit math: products
+/// are unsigned and the sum is tracked as sign and magnitude. Its MSB sign, `2^63 - rest`
+/// magnitude reading of the projections is [`Decoding::TwosComplement`].
+pub fn generate_lsh_circuit(features: &[u64], config: &LshConfig) -> LshHash {
+    lsh_circuit(features, &ProjectionMatrix::cached(config, features.len() as u64))
+}
+
+pub(crate) fn lsh_rust(features: &[u64], matrix: &ProjectionMatrix) -> LshHash {
+    assert_eq!(features.len() as u64, matrix.feature_count(), "feature count does not match the matrix");
+
+    let mut hash_res = LshHash::with_capacity(matrix.projection_count() as usize);
+
+    for row in matrix.rows() {
+        // i128 until it would overflow, the exact sum only matters for huge inputs
+        let mut final_sum: i128 = 0;
+        let mut big_sum: Option<BigInt> = None;
+
+        for (&feature, &projection) in features.iter().zip(row) {
+            let mult = feature as i128 * projection as i128;
+
+            match (&mut big_sum, final_sum.checked_add(mult)) {
+                (Some(big_sum), _) => *big_sum += mult,
+                (None, Some(sum)) => final_sum = sum,
+                (None, None) => big_sum = Some(BigInt::from(final_sum) + mult),
+            }
+        }
+
+        hash_res.push(match big_sum {
+            Some(big_sum) => big_sum < BigInt::from(0),
+            None => final_sum < 0,
+        });
+    }
+
+    hash_res
+}
+
+pub(crate) fn lsh_rust_f64(features: &[f64], matrix: &ProjectionMatrix) -> LshHash {
+    assert_eq!(features.len() as u64, matrix.feature_count(), "feature count does not match the matrix");
+
+    let mut hash_res = LshHash::with_capacity(matrix.projection_count() as usize);
+
+    for row in matrix.rows() {
+        hash_res.push(projection_sum_f64(features, row) < 0.0);
+    }
+
+    hash_res
+}
+
+/// Float projection sum of one row, in feature order so every implementation can match it bit for bit.
+pub(crate) fn projection_sum_f64(features: &[f64], row: &[i64]) -> f64 {
+    let mut final_sum = 0.0;
+
+    for (&feature, &projection) in features.iter().zip(row) {
+        let norm_projection = projection as f64 / 9223372036854775807.0; // 2^63 - 1
+
+        final_sum += feature * norm_projection;
+    }
+
+    final_sum
+}
+
+pub(crate) fn lsh_circuit(features: &[u64], matrix: &ProjectionMatrix) -> LshHash {
+    assert_eq!(features.len() as u64, matrix.feature_count(), "feature count does not match the matrix");
+
+    let mut hash_res = LshHash::with_capacity(matrix.projection_count() as usize);
+
+    for row in matrix.rows() {
+        let mut final_sum: u128 = 0;
+        let mut is_final_negative = false;
+
+        for (j, (&feature, &projection)) in features.iter().zip(row).enumerate() {
+            let is_projection_negative = projection < 0;
+            let projection = projection.unsigned_abs();
+
+            let mult = feature as u128 * projection as u128;
+
+            if j == 0 {
+                // assign initial values
+                final_sum = mult;
+                is_final_negative = is_projection_negative;
+            } else if is_final_negative != is_projection_negative && final_sum < mult {
+                final_sum = mult - final_sum;
+                is_final_negative = is_projection_negative;
+            } else if is_final_negative != is_projection_negative {
+                final_sum -= mult;
+            } else {
+                final_sum += mult;
+            }
+        }
+
+        hash_res.push(is_final_negative);
+    }
+
+    hash_res
+}
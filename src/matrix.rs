@@ -0,0 +1,248 @@
+//! Precomputed projection coefficients. Deriving a projection costs a Poseidon hash,
+//! so every (salt, projection count, feature count, source) matrix is built once per
+//! process and shared by every hash that needs it. With `LSH_MATRIX_DIR` set, matrices
+//! are loaded from (and can be generated into) that directory, see [`file`].
+
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::encoder::Features;
+use crate::field::lsh_field;
+use crate::hash::LshHash;
+use crate::lsh::{lsh_circuit, lsh_rust, lsh_rust_f64, LshConfig};
+use crate::projection::{Decoding, ProjectionSource};
+
+pub mod file;
+
+pub use file::MatrixFileError;
+
+/// `projection_count x feature_count` decoded coefficients, row `i` holds the projections
+/// of hash bit `i` for input indexes `input_index_offset..`.
+#[derive(Clone, Debug)]
+pub struct ProjectionMatrix {
+    source: &'static dyn ProjectionSource,
+    salt: u64,
+    projection_count: u64,
+    feature_count: u64,
+    input_index_offset: u64,
+    decoding: Decoding,
+    coefficients: Coefficients,
+}
+
+#[derive(Clone, Debug)]
+enum Coefficients {
+    Owned(Vec<i64>),
+    // little endian i64s starting at `offset`, checked to be aligned when loaded
+    Mapped { map: Arc<Mmap>, offset: usize, len: usize },
+}
+
+impl Coefficients {
+    fn as_slice(&self) -> &[i64] {
+        match self {
+            Coefficients::Owned(coefficients) => coefficients,
+            Coefficients::Mapped { map, offset, len } => {
+                let (prefix, coefficients, _) = unsafe { map[*offset..].align_to::<i64>() };
+                debug_assert!(prefix.is_empty());
+
+                &coefficients[..*len]
+            }
+        }
+    }
+}
+
+// sources are identified by name, the built-in ones are unit structs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct MatrixKey {
+    source: &'static str,
+    salt: u64,
+    projection_count: u64,
+    feature_count: u64,
+    input_index_offset: u64,
+    decoding: Decoding,
+}
+
+static CACHE: OnceLock<Mutex<HashMap<MatrixKey, Arc<ProjectionMatrix>>>> = OnceLock::new();
+
+impl ProjectionMatrix {
+    /// Derives every coefficient from `config.source` and decodes it, rows are split over all cores.
+    pub fn new(config: &LshConfig, feature_count: u64) -> Self {
+        let mut coefficients = vec![0i64; (config.projection_count * feature_count) as usize];
+
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+        let rows_per_thread = config.projection_count.div_ceil(threads).max(1);
+        let chunk_len = (rows_per_thread * feature_count).max(1) as usize;
+
+        std::thread::scope(|scope| {
+            for (chunk_index, chunk) in coefficients.chunks_mut(chunk_len).enumerate() {
+                let first_row = chunk_index as u64 * rows_per_thread;
+
+                scope.spawn(move || {
+                    for (offset, coefficient) in chunk.iter_mut().enumerate() {
+                        let i = first_row + offset as u64 / feature_count;
+                        let input_index = config.input_index_offset + offset as u64 % feature_count;
+
+                        *coefficient = config.decoding.decode(config.source.projection(config.salt.value(), i, input_index));
+                    }
+                });
+            }
+        });
+
+        ProjectionMatrix {
+            source: config.source,
+            salt: config.salt.value(),
+            projection_count: config.projection_count,
+            feature_count,
+            input_index_offset: config.input_index_offset,
+            decoding: config.decoding,
+            coefficients: Coefficients::Owned(coefficients),
+        }
+    }
+
+    /// Returns the process wide matrix for `config` and `feature_count`. On first use it
+    /// is loaded from `LSH_MATRIX_DIR` if that holds a file for it, otherwise built.
+    /// Panics if that file is corrupt or does not match, a stale matrix is never used.
+    pub fn cached(config: &LshConfig, feature_count: u64) -> Arc<ProjectionMatrix> {
+        let key = MatrixKey {
+            source: config.source.name(),
+            salt: config.salt.value(),
+            projection_count: config.projection_count,
+            feature_count,
+            input_index_offset: config.input_index_offset,
+            decoding: config.decoding,
+        };
+
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        if let Some(matrix) = cache.lock().unwrap().get(&key) {
+            return matrix.clone();
+        }
+
+        // built outside the lock, a racing thread at worst builds the same matrix twice
+        let matrix = match std::env::var_os("LSH_MATRIX_DIR") {
+            Some(dir) if Path::new(&dir).join(file::file_name(config, feature_count)).exists() => {
+                let path = Path::new(&dir).join(file::file_name(config, feature_count));
+
+                ProjectionMatrix::load_for(&path, config, feature_count)
+                    .unwrap_or_else(|err| panic!("{}: {} (regenerate it with projection_matrix generate)", path.display(), err))
+            }
+            _ => ProjectionMatrix::new(config, feature_count),
+        };
+        let matrix = Arc::new(matrix);
+        cache.lock().unwrap().entry(key).or_insert(matrix).clone()
+    }
+
+    /// Puts `matrix` into the process wide cache, e.g. after loading it from a file.
+    pub fn preload(matrix: ProjectionMatrix) -> Arc<ProjectionMatrix> {
+        let key = MatrixKey {
+            source: matrix.source.name(),
+            salt: matrix.salt,
+            projection_count: matrix.projection_count,
+            feature_count: matrix.feature_count,
+            input_index_offset: matrix.input_index_offset,
+            decoding: matrix.decoding,
+        };
+
+        let matrix = Arc::new(matrix);
+        CACHE.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap().insert(key, matrix.clone());
+
+        matrix
+    }
+
+    /// Drops every cached matrix.
+    pub fn clear_cache() {
+        if let Some(cache) = CACHE.get() {
+            cache.lock().unwrap().clear();
+        }
+    }
+
+    pub fn source(&self) -> &'static dyn ProjectionSource {
+        self.source
+    }
+
+    pub fn salt(&self) -> u64 {
+        self.salt
+    }
+
+    pub fn projection_count(&self) -> u64 {
+        self.projection_count
+    }
+
+    pub fn feature_count(&self) -> u64 {
+        self.feature_count
+    }
+
+    pub fn input_index_offset(&self) -> u64 {
+        self.input_index_offset
+    }
+
+    pub fn decoding(&self) -> Decoding {
+        self.decoding
+    }
+
+    /// Whether this matrix holds the projections `config` would derive for `feature_count` features.
+    pub fn matches(&self, config: &LshConfig, feature_count: u64) -> bool {
+        self.source.name() == config.source.name()
+            && self.salt == config.salt.value()
+            && self.projection_count == config.projection_count
+            && self.feature_count == feature_count
+            && self.input_index_offset == config.input_index_offset
+            && self.decoding == config.decoding
+    }
+
+    /// Coefficient of projection `projection_index` for feature `feature_index` (not the
+    /// input index, the offset is already applied).
+    pub fn get(&self, projection_index: u64, feature_index: u64) -> i64 {
+        self.coefficients()[(projection_index * self.feature_count + feature_index) as usize]
+    }
+
+    pub fn row(&self, projection_index: u64) -> &[i64] {
+        let start = (projection_index * self.feature_count) as usize;
+
+        &self.coefficients()[start..start + self.feature_count as usize]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[i64]> {
+        // chunks() panics on 0, a matrix without features still has empty rows
+        (0..self.projection_count).map(|i| self.row(i))
+    }
+
+    /// Row major coefficients.
+    pub fn coefficients(&self) -> &[i64] {
+        self.coefficients.as_slice()
+    }
+
+    /// Same parameters with every coefficient passed through `f`, e.g. to narrow them the
+    /// way a circuit does. Never put the result into the cache, it no longer matches its source.
+    pub(crate) fn map_coefficients(&self, f: impl Fn(i64) -> i64) -> ProjectionMatrix {
+        ProjectionMatrix { coefficients: Coefficients::Owned(self.coefficients().iter().map(|&c| f(c)).collect()), ..self.clone() }
+    }
+
+    /// [`generate_lsh_rust`](crate::generate_lsh_rust) with this matrix.
+    pub fn hash_rust(&self, features: &[u64]) -> LshHash {
+        lsh_rust(features, self)
+    }
+
+    /// [`generate_lsh_rust_f64`](crate::generate_lsh_rust_f64) with this matrix.
+    pub fn hash_rust_f64(&self, features: &[f64]) -> LshHash {
+        lsh_rust_f64(features, self)
+    }
+
+    /// [`generate_lsh_circuit`](crate::generate_lsh_circuit) with this matrix.
+    pub fn hash_circuit(&self, features: &[u64]) -> LshHash {
+        lsh_circuit(features, self)
+    }
+
+    /// [`generate_lsh_field`](crate::generate_lsh_field) with this matrix.
+    pub fn hash_field(&self, features: &[u64]) -> LshHash {
+        lsh_field(features, self)
+    }
+
+    /// [`generate_lsh`](crate::generate_lsh) with this matrix.
+    pub fn hash(&self, features: &Features) -> LshHash {
+        match features {
+            Features::Integer(features) => self.hash_rust(features),
+            Features::Float(features) => self.hash_rust_f64(features),
+        }
+    }
+}
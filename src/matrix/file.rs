@@ -0,0 +1,215 @@
+//! Versioned on-disk format for [`ProjectionMatrix`], so a batch of experiment runs can
+//! map the projections instead of re-deriving millions of Poseidon hashes each.
+//!
+//! Layout, all integers little endian:
+//!
+//! | offset | size | field                                               |
+//! |--------|------|-----------------------------------------------------|
+//! | 0      | 8    | magic `LSHPMAT\0`                                   |
+//! | 8      | 4    | format version (1)                                  |
+//! | 12     | 4    | decoding, 0 two's complement, 1 drop MSB            |
+//! | 16     | 16   | source name, zero padded                            |
+//! | 32     | 8    | salt                                                |
+//! | 40     | 8    | projection count                                    |
+//! | 48     | 8    | feature count                                       |
+//! | 56     | 8    | input index offset                                  |
+//! | 64     | 32   | sha256 of bytes 0..64 followed by the coefficients |
+//! | 96     | 8n   | decoded coefficients as i64, row major              |
+
+use memmap2::Mmap;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::{Coefficients, ProjectionMatrix};
+use crate::lsh::LshConfig;
+use crate::projection::{source_by_name, Decoding};
+
+pub const MAGIC: [u8; 8] = *b"LSHPMAT\0";
+pub const VERSION: u32 = 1;
+pub const HEADER_LEN: usize = 96;
+
+const SOURCE_NAME_LEN: usize = 16;
+const CHECKSUM_OFFSET: usize = 64;
+
+#[derive(Debug)]
+pub enum MatrixFileError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnknownSource(String),
+    UnknownDecoding(u32),
+    /// File length does not match the dimensions in the header.
+    Truncated { expected: u64, actual: u64 },
+    ChecksumMismatch,
+    /// Valid file, but for other parameters than the ones asked for.
+    ParameterMismatch,
+}
+
+impl fmt::Display for MatrixFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixFileError::Io(err) => write!(f, "io error: {}", err),
+            MatrixFileError::BadMagic => write!(f, "not a projection matrix file"),
+            MatrixFileError::UnsupportedVersion(version) => write!(f, "unsupported format version {}", version),
+            MatrixFileError::UnknownSource(name) => write!(f, "unknown projection source '{}'", name),
+            MatrixFileError::UnknownDecoding(id) => write!(f, "unknown decoding {}", id),
+            MatrixFileError::Truncated { expected, actual } => write!(f, "expected {} bytes, file has {}", expected, actual),
+            MatrixFileError::ChecksumMismatch => write!(f, "checksum mismatch"),
+            MatrixFileError::ParameterMismatch => write!(f, "matrix was generated for different parameters"),
+        }
+    }
+}
+
+impl std::error::Error for MatrixFileError {}
+
+impl From<io::Error> for MatrixFileError {
+    fn from(err: io::Error) -> Self {
+        MatrixFileError::Io(err)
+    }
+}
+
+/// Default file name for the matrix of `config` and `feature_count`, used for `LSH_MATRIX_DIR`.
+pub fn file_name(config: &LshConfig, feature_count: u64) -> String {
+    format!(
+        "{}-salt{}-{}x{}-offset{}-{}.lshm",
+        config.source.name(),
+        config.salt.value(),
+        config.projection_count,
+        feature_count,
+        config.input_index_offset,
+        config.decoding.name()
+    )
+}
+
+fn header_fields(matrix: &ProjectionMatrix) -> [u8; CHECKSUM_OFFSET] {
+    let mut header = [0u8; CHECKSUM_OFFSET];
+
+    let name = matrix.source.name().as_bytes();
+    assert!(name.len() <= SOURCE_NAME_LEN, "source name too long for the file header");
+
+    header[..8].copy_from_slice(&MAGIC);
+    header[8..12].copy_from_slice(&VERSION.to_le_bytes());
+    header[12..16].copy_from_slice(&decoding_id(matrix.decoding).to_le_bytes());
+    header[16..16 + name.len()].copy_from_slice(name);
+    header[32..40].copy_from_slice(&matrix.salt.to_le_bytes());
+    header[40..48].copy_from_slice(&matrix.projection_count.to_le_bytes());
+    header[48..56].copy_from_slice(&matrix.feature_count.to_le_bytes());
+    header[56..64].copy_from_slice(&matrix.input_index_offset.to_le_bytes());
+
+    header
+}
+
+fn decoding_id(decoding: Decoding) -> u32 {
+    match decoding {
+        Decoding::TwosComplement => 0,
+        Decoding::DropMsb => 1,
+    }
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+impl ProjectionMatrix {
+    /// Writes the matrix in the format described in [`file`](self).
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let header = header_fields(self);
+
+        let mut hasher = Sha256::new();
+        hasher.update(header);
+        for coefficient in self.coefficients() {
+            hasher.update(coefficient.to_le_bytes());
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&header)?;
+        writer.write_all(&hasher.finalize())?;
+        for coefficient in self.coefficients() {
+            writer.write_all(&coefficient.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Memory maps a matrix file after checking the header, length and checksum.
+    pub fn load(path: &Path) -> Result<ProjectionMatrix, MatrixFileError> {
+        let map = unsafe { Mmap::map(&File::open(path)?)? };
+
+        if map.len() < HEADER_LEN {
+            return Err(MatrixFileError::Truncated { expected: HEADER_LEN as u64, actual: map.len() as u64 });
+        }
+        if map[..8] != MAGIC {
+            return Err(MatrixFileError::BadMagic);
+        }
+
+        let version = u32::from_le_bytes(map[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(MatrixFileError::UnsupportedVersion(version));
+        }
+
+        let id = u32::from_le_bytes(map[12..16].try_into().unwrap());
+        let decoding = Decoding::ALL.into_iter().find(|&decoding| decoding_id(decoding) == id).ok_or(MatrixFileError::UnknownDecoding(id))?;
+
+        let name_bytes = &map[16..16 + SOURCE_NAME_LEN];
+        let name = String::from_utf8_lossy(&name_bytes[..name_bytes.iter().position(|&b| b == 0).unwrap_or(SOURCE_NAME_LEN)]);
+        let source = source_by_name(&name).ok_or_else(|| MatrixFileError::UnknownSource(name.to_string()))?;
+
+        let salt = read_u64(&map, 32);
+        let projection_count = read_u64(&map, 40);
+        let feature_count = read_u64(&map, 48);
+        let input_index_offset = read_u64(&map, 56);
+
+        let len = projection_count.checked_mul(feature_count);
+        let expected = len.and_then(|len| len.checked_mul(8)).and_then(|bytes| bytes.checked_add(HEADER_LEN as u64));
+        if expected != Some(map.len() as u64) {
+            return Err(MatrixFileError::Truncated { expected: expected.unwrap_or(u64::MAX), actual: map.len() as u64 });
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(&map[..CHECKSUM_OFFSET]);
+        hasher.update(&map[HEADER_LEN..]);
+        if hasher.finalize()[..] != map[CHECKSUM_OFFSET..HEADER_LEN] {
+            return Err(MatrixFileError::ChecksumMismatch);
+        }
+
+        let len = len.unwrap() as usize;
+        // the map is page aligned so the coefficients are too, copy if that ever fails
+        let (prefix, _, _) = unsafe { map[HEADER_LEN..].align_to::<i64>() };
+        let coefficients = if cfg!(target_endian = "little") && prefix.is_empty() {
+            Coefficients::Mapped { map: Arc::new(map), offset: HEADER_LEN, len }
+        } else {
+            Coefficients::Owned(map[HEADER_LEN..].chunks_exact(8).map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap())).collect())
+        };
+
+        Ok(ProjectionMatrix { source, salt, projection_count, feature_count, input_index_offset, decoding, coefficients })
+    }
+
+    /// [`ProjectionMatrix::load`] that also rejects a matrix generated for other parameters.
+    pub fn load_for(path: &Path, config: &LshConfig, feature_count: u64) -> Result<ProjectionMatrix, MatrixFileError> {
+        let matrix = ProjectionMatrix::load(path)?;
+
+        if !matrix.matches(config, feature_count) {
+            return Err(MatrixFileError::ParameterMismatch);
+        }
+
+        Ok(matrix)
+    }
+
+    /// Re-derives `samples` coefficients spread over the matrix from its source and
+    /// returns the (projection, feature) of the first one that differs.
+    pub fn spot_check(&self, samples: u64) -> Option<(u64, u64)> {
+        let total = self.projection_count * self.feature_count;
+        let step = (total / samples.max(1)).max(1);
+
+        (0..total).step_by(step as usize).chain(total.checked_sub(1)).find_map(|index| {
+            let (i, j) = (index / self.feature_count, index % self.feature_count);
+            let expected = self.decoding.decode(self.source.projection(self.salt, i, self.input_index_offset + j));
+
+            (self.get(i, j) != expected).then_some((i, j))
+        })
+    }
+}
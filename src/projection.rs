@@ -0,0 +1,126 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use solana_poseidon::{hashv, Endianness, Parameters};
+use std::fmt::Debug;
+
+pub mod divergence;
+pub mod equivalence;
+
+/// Yields the signed projection coefficient for (salt, projection index, feature index).
+pub trait ProjectionSource: Debug + Sync {
+    /// Short identifier, e.g. for file headers and reports.
+    fn name(&self) -> &'static str;
+
+    fn projection(&self, salt: u64, projection_index: u64, input_index: u64) -> i64;
+}
+
+/// Poseidon (BN254, x^5) over `[salt, projection_index, input_index]`, matches the circuit.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Poseidon;
+
+/// ChaCha8 seeded with `[salt, projection_index, input_index, 0]`. Much faster than
+/// Poseidon but not what the circuit computes, use it for large exploratory sweeps.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChaCha8;
+
+impl ProjectionSource for Poseidon {
+    fn name(&self) -> &'static str {
+        "poseidon"
+    }
+
+    fn projection(&self, salt: u64, projection_index: u64, input_index: u64) -> i64 {
+        poseidon_projection(salt, projection_index, input_index)
+    }
+}
+
+impl ProjectionSource for ChaCha8 {
+    fn name(&self) -> &'static str {
+        "chacha8"
+    }
+
+    fn projection(&self, salt: u64, projection_index: u64, input_index: u64) -> i64 {
+        let mut seed = [0u8; 32];
+        seed[..8].copy_from_slice(&salt.to_le_bytes());
+        seed[8..16].copy_from_slice(&projection_index.to_le_bytes());
+        seed[16..24].copy_from_slice(&input_index.to_le_bytes());
+
+        let mut rng = ChaCha8Rng::from_seed(seed);
+
+        rng.random_range(i64::MIN..i64::MAX)
+    }
+}
+
+/// How the raw 64 projection bits (the i64 a [`ProjectionSource`] returns) become a
+/// signed coefficient.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Decoding {
+    /// `i64::from_le_bytes`, what `generate_lsh_rust` always used.
+    #[default]
+    TwosComplement,
+    /// MSB is the sign, magnitude is the remaining 63 bits, what circom does today.
+    DropMsb,
+}
+
+impl Decoding {
+    /// What the circom circuit decodes with.
+    pub const CIRCUIT: Decoding = Decoding::DropMsb;
+
+    pub const ALL: [Decoding; 2] = [Decoding::TwosComplement, Decoding::DropMsb];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Decoding::TwosComplement => "twos_complement",
+            Decoding::DropMsb => "drop_msb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Decoding> {
+        Decoding::ALL.into_iter().find(|decoding| decoding.name() == name)
+    }
+
+    /// Signed coefficient for the raw projection bits.
+    pub fn decode(&self, raw: i64) -> i64 {
+        let bits = raw as u64;
+        let is_negative = bits >> 63 == 1;
+        let rest = bits & ((1 << 63) - 1);
+
+        match (self, is_negative) {
+            (Decoding::TwosComplement, _) => raw,
+            (_, false) => rest as i64,
+            (Decoding::DropMsb, true) => -(rest as i64),
+        }
+    }
+}
+
+/// Looks up a built-in source by its [`ProjectionSource::name`].
+pub fn source_by_name(name: &str) -> Option<&'static dyn ProjectionSource> {
+    match name {
+        "poseidon" => Some(&Poseidon),
+        "chacha8" => Some(&ChaCha8),
+        _ => None,
+    }
+}
+
+/// Poseidon hash of [salt, projection_index, input_index] (each u64 little endian),
+/// the same seed layout the circuit uses to derive its projections.
+pub fn poseidon_projection_bytes(salt: u64, projection_index: u64, input_index: u64) -> [u8; 32] {
+    let salt_bytes = u64::to_le_bytes(salt);
+    let projection_index_bytes = u64::to_le_bytes(projection_index);
+    let input_index_bytes = u64::to_le_bytes(input_index);
+
+    let dim0: &[&[u8]] = &[&salt_bytes, &projection_index_bytes, &input_index_bytes];
+
+    hashv(Parameters::Bn254X5, Endianness::LittleEndian, dim0)
+        .unwrap()
+        .to_bytes()
+}
+
+/// Signed projection value, the first 8 bytes of the poseidon hash read as i64.
+pub fn poseidon_projection(salt: u64, projection_index: u64, input_index: u64) -> i64 {
+    let pos_hash_bytes = poseidon_projection_bytes(salt, projection_index, input_index);
+
+    let mut array0 = [0u8; 8];
+    array0.copy_from_slice(&pos_hash_bytes[..8]);
+
+    i64::from_le_bytes(array0)
+}
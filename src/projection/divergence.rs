@@ -0,0 +1,101 @@
+//! Which hash bits change when the projections are decoded differently, so the decoding
+//! the circuit should use can be picked from data.
+
+use std::fmt;
+
+use super::Decoding;
+use crate::encoder::Features;
+use crate::hash::LshHash;
+use crate::lsh::{generate_lsh, LshConfig};
+
+/// Differences between the hashes of two decodings over the same inputs.
+#[derive(Clone, Debug)]
+pub struct ModeDivergence {
+    pub modes: [Decoding; 2],
+    /// Indexes of the differing hash bits, one entry per input.
+    pub differing_bits: Vec<Vec<usize>>,
+    /// Number of inputs each hash bit differs on.
+    pub bit_counts: Vec<usize>,
+}
+
+impl ModeDivergence {
+    pub fn diverging_inputs(&self) -> usize {
+        self.differing_bits.iter().filter(|bits| !bits.is_empty()).count()
+    }
+
+    pub fn differing_bit_total(&self) -> usize {
+        self.differing_bits.iter().map(Vec::len).sum()
+    }
+}
+
+/// Result of [`decoding_divergence`], one [`ModeDivergence`] per pair of decodings.
+#[derive(Clone, Debug)]
+pub struct DivergenceReport {
+    pub inputs: usize,
+    pub projection_count: u64,
+    pub pairs: Vec<ModeDivergence>,
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "inputs: {} | projections: {}", self.inputs, self.projection_count)?;
+
+        for pair in &self.pairs {
+            writeln!(
+                f,
+                "{} vs {}: {} of {} inputs differ, {} bits total ({:.2} per input)",
+                pair.modes[0].name(),
+                pair.modes[1].name(),
+                pair.diverging_inputs(),
+                self.inputs,
+                pair.differing_bit_total(),
+                pair.differing_bit_total() as f64 / self.inputs.max(1) as f64,
+            )?;
+
+            for (input, bits) in pair.differing_bits.iter().enumerate().filter(|(_, bits)| !bits.is_empty()) {
+                writeln!(f, "  input {}: bits {:?}", input, bits)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Hashes every input with every decoding in `modes` (the rest of `config` unchanged)
+/// and lists the hash bits that differ for each pair of modes.
+pub fn decoding_divergence(inputs: &[Features], config: &LshConfig, modes: &[Decoding]) -> DivergenceReport {
+    let hashes: Vec<Vec<LshHash>> = modes
+        .iter()
+        .map(|&decoding| {
+            let config = LshConfig { decoding, ..*config };
+
+            inputs.iter().map(|features| generate_lsh(features, &config)).collect()
+        })
+        .collect();
+
+    let mut pairs = Vec::new();
+    for a in 0..modes.len() {
+        for b in a + 1..modes.len() {
+            let mut bit_counts = vec![0; config.projection_count as usize];
+
+            let differing_bits = hashes[a]
+                .iter()
+                .zip(&hashes[b])
+                .map(|(hash_a, hash_b)| {
+                    let xor = hash_a ^ hash_b;
+                    let bits: Vec<usize> = xor.iter().enumerate().filter(|&(_, bit)| bit).map(|(i, _)| i).collect();
+
+                    for &bit in &bits {
+                        bit_counts[bit] += 1;
+                    }
+
+                    bits
+                })
+                .collect();
+
+            pairs.push(ModeDivergence { modes: [modes[a], modes[b]], differing_bits, bit_counts });
+        }
+    }
+
+    DivergenceReport { inputs: inputs.len(), projection_count: config.projection_count, pairs }
+}
@@ -0,0 +1,169 @@
+//! Statistical check that two projection sources can be swapped for each other, so
+//! results from fast ChaCha8 sweeps can be trusted for the Poseidon circuit.
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+use super::ProjectionSource;
+use crate::lsh::LshConfig;
+use crate::matrix::ProjectionMatrix;
+use crate::salt::Salt;
+
+// every z-score has to stay below this for the sources to count as equivalent
+const Z_LIMIT: f64 = 4.0;
+// two sample Kolmogorov-Smirnov coefficient for alpha = 0.001
+const KS_ALPHA_0_001: f64 = 1.949;
+
+/// Result of [`check_equivalence`]. Index 0 of the per-source arrays is source `a`.
+#[derive(Clone, Debug)]
+pub struct EquivalenceReport {
+    pub names: [&'static str; 2],
+    /// Coefficients drawn from each source.
+    pub samples: usize,
+    /// Mean of the coefficients scaled to [-1, 1], expected 0.
+    pub means: [f64; 2],
+    /// Variance of the scaled coefficients, expected 1/3 for a uniform source.
+    pub variances: [f64; 2],
+    pub mean_z: [f64; 2],
+    pub variance_z: [f64; 2],
+    /// Two sample Kolmogorov-Smirnov distance between the coefficient distributions.
+    pub ks_statistic: f64,
+    pub ks_critical: f64,
+    /// Average fraction of differing hash bits over the test pairs.
+    pub collision_rates: [f64; 2],
+    /// Two sample z-score of the collision rates, over projection rows.
+    pub collision_z: f64,
+}
+
+impl EquivalenceReport {
+    pub fn is_equivalent(&self) -> bool {
+        self.mean_z.iter().chain(self.variance_z.iter()).all(|z| z.abs() < Z_LIMIT)
+            && self.ks_statistic < self.ks_critical
+            && self.collision_z.abs() < Z_LIMIT
+    }
+}
+
+impl fmt::Display for EquivalenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "samples per source: {}", self.samples)?;
+        for i in 0..2 {
+            writeln!(
+                f,
+                "{}: mean {:.5} (z {:.2}) | variance {:.5} (z {:.2}) | collision rate {:.5}",
+                self.names[i], self.means[i], self.mean_z[i], self.variances[i], self.variance_z[i], self.collision_rates[i],
+            )?;
+        }
+        writeln!(f, "ks: {:.5} (critical {:.5})", self.ks_statistic, self.ks_critical)?;
+        writeln!(f, "collision z: {:.2}", self.collision_z)?;
+        write!(f, "equivalent: {}", self.is_equivalent())
+    }
+}
+
+/// Draws `projection_count * feature_count` coefficients from both sources and
+/// compares their distributions (moments and Kolmogorov-Smirnov), then hashes
+/// `pairs` random feature vector pairs with both and compares how many bits differ.
+pub fn check_equivalence(
+    a: &'static dyn ProjectionSource,
+    b: &'static dyn ProjectionSource,
+    salt: Salt,
+    projection_count: u64,
+    feature_count: u64,
+    pairs: usize,
+) -> EquivalenceReport {
+    let matrices = [a, b].map(|source| scaled_coefficients(source, salt, projection_count, feature_count));
+    let samples = matrices[0].len();
+
+    let means = matrices.each_ref().map(|m| m.iter().sum::<f64>() / samples as f64);
+    let variances = [0, 1].map(|i| matrices[i].iter().map(|c| (c - means[i]).powi(2)).sum::<f64>() / samples as f64);
+
+    // uniform on [-1, 1]: Var(X) = 1/3, Var(X^2) = 1/5 - 1/9
+    let mean_z = means.map(|mean| mean / (1.0 / 3.0 / samples as f64).sqrt());
+    let variance_z = variances.map(|variance| (variance - 1.0 / 3.0) / (4.0 / 45.0 / samples as f64).sqrt());
+
+    let ks_statistic = ks_distance(&matrices[0], &matrices[1]);
+    let ks_critical = KS_ALPHA_0_001 * (2.0 / samples as f64).sqrt();
+
+    // random pairs at increasing distances, counted per projection row since every row
+    // is reused for all pairs (rows are the independent samples, not bits)
+    let mut rng = ChaCha8Rng::seed_from_u64(salt.value());
+    let mut row_differing = [vec![0u64; projection_count as usize], vec![0u64; projection_count as usize]];
+
+    for k in 0..pairs {
+        let scale = [0.05, 0.1, 0.2, 0.5, 1.0][k % 5];
+        let x: Vec<f64> = (0..feature_count).map(|_| rng.random_range(-1.0..1.0)).collect();
+        let y: Vec<f64> = x.iter().map(|v| v + scale * rng.random_range(-1.0..1.0)).collect();
+
+        for (m, counts) in matrices.iter().zip(row_differing.iter_mut()) {
+            for (row, count) in m.chunks(feature_count as usize).zip(counts.iter_mut()) {
+                if (dot(row, &x) < 0.0) != (dot(row, &y) < 0.0) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let rows = projection_count as f64;
+    let row_rates = row_differing.map(|counts| counts.iter().map(|&c| c as f64 / pairs.max(1) as f64).collect::<Vec<f64>>());
+    let collision_rates = row_rates.each_ref().map(|rates| rates.iter().sum::<f64>() / rows);
+    let rate_variances = [0, 1].map(|i| row_rates[i].iter().map(|r| (r - collision_rates[i]).powi(2)).sum::<f64>() / (rows - 1.0).max(1.0));
+
+    let standard_error = ((rate_variances[0] + rate_variances[1]) / rows).sqrt();
+    let collision_z = if standard_error == 0.0 {
+        0.0
+    } else {
+        (collision_rates[0] - collision_rates[1]) / standard_error
+    };
+
+    EquivalenceReport {
+        names: [a.name(), b.name()],
+        samples,
+        means,
+        variances,
+        mean_z,
+        variance_z,
+        ks_statistic,
+        ks_critical,
+        collision_rates,
+        collision_z,
+    }
+}
+
+// row major projection_count x feature_count, scaled to [-1, 1]
+fn scaled_coefficients(source: &'static dyn ProjectionSource, salt: Salt, projection_count: u64, feature_count: u64) -> Vec<f64> {
+    let config = LshConfig { source, ..LshConfig::new(projection_count, salt) };
+
+    ProjectionMatrix::new(&config, feature_count)
+        .coefficients()
+        .iter()
+        .map(|&coefficient| coefficient as f64 / 9223372036854775808.0) // 2^63
+        .collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+// largest distance between the two empirical CDFs
+fn ks_distance(a: &[f64], b: &[f64]) -> f64 {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_by(f64::total_cmp);
+    b.sort_by(f64::total_cmp);
+
+    let (mut i, mut j) = (0, 0);
+    let mut max_distance: f64 = 0.0;
+
+    while i < a.len() && j < b.len() {
+        if a[i] <= b[j] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+
+        let distance = (i as f64 / a.len() as f64 - j as f64 / b.len() as f64).abs();
+        max_distance = max_distance.max(distance);
+    }
+
+    max_distance
+}
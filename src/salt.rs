@@ -0,0 +1,142 @@
+//! Salts for the projections. With a public salt anyone can recompute the projections
+//! and search for the output behind a commitment, so a salt has to be secret and fresh
+//! per order:
+//!
+//! - [`Salt::generate`] draws one from a cryptographic RNG
+//! - [`Salt::derive`] derives one deterministically from a user secret and an order nonce
+//!   with Poseidon, so a wallet can recover it without storing it
+//! - [`Salt::commitment`] is Poseidon(salt, blinding), what public outputs carry instead
+//!   of the salt. The blinding keeps the 64 bit salt from being brute forced out of it
+//!
+//! Salt 0 is what every experiment used. [`LshConfig`](crate::LshConfig) and so every
+//! hasher, matrix and circuit takes its salt as a [`Salt`], which is never 0 except
+//! through [`Salt::test`], the explicit test mode, or [`Salt::recorded`] replaying it.
+//! Circuits with a salt input bind it the same way, see [`circom::codegen`](crate::circom::codegen).
+
+use ark_ff::PrimeField;
+use light_poseidon::{Poseidon, PoseidonHasher};
+use rand::{CryptoRng, RngCore};
+use std::fmt;
+
+use crate::field::Fr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaltError {
+    /// Salt 0 outside of [`Salt::test`].
+    Zero,
+}
+
+impl fmt::Display for SaltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaltError::Zero => write!(f, "salt 0 makes the projections public, it is only allowed in test mode"),
+        }
+    }
+}
+
+impl std::error::Error for SaltError {}
+
+/// A projection salt with the blinding of its commitment.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Salt {
+    value: u64,
+    blinding: Fr,
+}
+
+/// Leaves the blinding out, it only protects the commitment while it stays secret and
+/// `Salt` ends up in the debug output of every config holding one.
+impl fmt::Debug for Salt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Salt").field("value", &self.value).field("blinding", &format_args!("<redacted>")).finish()
+    }
+}
+
+impl Salt {
+    /// Salt from known parts, salt 0 is rejected.
+    pub fn new(value: u64, blinding: Fr) -> Result<Self, SaltError> {
+        if value == 0 {
+            return Err(SaltError::Zero);
+        }
+
+        Ok(Salt { value, blinding })
+    }
+
+    /// Salt `value` with blinding 0, e.g. from the command line. 0 is rejected.
+    pub fn unblinded(value: u64) -> Result<Self, SaltError> {
+        Self::new(value, Fr::from(0u64))
+    }
+
+    /// Salt of an already recorded hash (test vectors, circuit witnesses), 0 included. Only
+    /// for replaying hashes, a new order needs [`Salt::generate`] or [`Salt::derive`].
+    pub fn recorded(value: u64, blinding: Fr) -> Self {
+        Salt { value, blinding }
+    }
+
+    /// Fresh salt and blinding from `rng`.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let value = std::iter::repeat_with(|| rng.next_u64()).find(|&value| value != 0).unwrap();
+
+        // 512 bits reduced mod p, the bias is negligible
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+
+        Salt { value, blinding: Fr::from_le_bytes_mod_order(&bytes) }
+    }
+
+    /// [`Salt::generate`] with the thread local RNG.
+    pub fn random() -> Self {
+        Self::generate(&mut rand::rng())
+    }
+
+    /// Salt Poseidon(secret, nonce) and blinding Poseidon(secret, nonce, 1). The salt is the
+    /// first non zero 64 bit limb of the hash, the low one unless it is 0.
+    pub fn derive(secret: Fr, nonce: u64) -> Self {
+        let salt_hash = poseidon(&[secret, Fr::from(nonce)]);
+        let value = salt_hash.into_bigint().0.into_iter().find(|&limb| limb != 0).unwrap_or(1);
+
+        Salt { value, blinding: poseidon(&[secret, Fr::from(nonce), Fr::from(1u64)]) }
+    }
+
+    /// Salt 0 with blinding 0, what the experiments and the pinned test vectors use.
+    pub fn test() -> Self {
+        Salt { value: 0, blinding: Fr::from(0u64) }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn blinding(&self) -> Fr {
+        self.blinding
+    }
+
+    pub fn is_test(&self) -> bool {
+        self.value == 0
+    }
+
+    /// See [`salt_commitment`].
+    pub fn commitment(&self) -> Fr {
+        salt_commitment(self.value, self.blinding)
+    }
+}
+
+/// Poseidon(salt, blinding), circomlib compatible like the projections.
+pub fn salt_commitment(salt: u64, blinding: Fr) -> Fr {
+    poseidon(&[Fr::from(salt), blinding])
+}
+
+fn poseidon(inputs: &[Fr]) -> Fr {
+    Poseidon::<Fr>::new_circom(inputs.len()).and_then(|mut poseidon| poseidon.hash(inputs)).expect("Poseidon supports 2 and 3 inputs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_the_blinding() {
+        let salt = Salt::new(5, Fr::from(123_456_789u64)).unwrap();
+
+        assert_eq!(format!("{:?}", salt), "Salt { value: 5, blinding: <redacted> }");
+    }
+}
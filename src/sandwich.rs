@@ -0,0 +1,251 @@
+//! Sandwich attacks against a [`Pool`]: the attacker front-runs the victim in the same
+//! direction, the victim swaps at the worse price and the attacker sells everything the
+//! front-run bought back into the pool. Fees are whatever the pool model charges.
+//!
+//! Each run is checked against the victim's commitment (the base, upper and lower hashes
+//! of [`get_pool_boundary_hashes`]) with the rule of [`commitment`](crate::commitment): an
+//! output is rejected once its hash is at least as far from either boundary hash as the
+//! two boundaries are from each other. What matters is profitable and not rejected.
+
+use std::fmt;
+
+use crate::amm::{max_front_run, Pool};
+use crate::boundary::{get_pool_boundary_hashes, get_pool_hash};
+use crate::commitment::{DecisionRule, Verdict};
+use crate::hash::LshHash;
+
+/// The trade being protected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VictimTrade {
+    pub is_swap_x_to_y: bool,
+    pub input_amount: u64,
+    /// 10,000 = 100%
+    pub slippage: u64,
+}
+
+/// What the victim commits to before trading: the hashes of the quoted output and of
+/// its slippage boundaries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VictimCommitment {
+    pub output: u64,
+    pub upper_output: u64,
+    pub lower_output: u64,
+    pub base_hash: LshHash,
+    pub upper_hash: LshHash,
+    pub lower_hash: LshHash,
+}
+
+impl VictimCommitment {
+    /// Commitment for `victim` at the current state of `pool`, `None` if the pool can't fill it.
+    pub fn new<F: Fn(u64) -> LshHash>(pool: &dyn Pool, victim: &VictimTrade, lsh: F) -> Option<Self> {
+        let ([upper_hash, lower_hash], upper_output, lower_output, output) =
+            get_pool_boundary_hashes(pool, victim.is_swap_x_to_y, victim.input_amount, victim.slippage, &lsh)?;
+        let (base_hash, _) = get_pool_hash(pool, victim.is_swap_x_to_y, victim.input_amount, &lsh)?;
+
+        Some(VictimCommitment { output, upper_output, lower_output, base_hash, upper_hash, lower_hash })
+    }
+
+    pub fn boundary_distance(&self) -> u32 {
+        self.upper_hash.hamming_distance(&self.lower_hash)
+    }
+
+    /// The experiments' crossing rule, [`DecisionRule::PerSide`].
+    pub fn rejects(&self, hash: &LshHash) -> bool {
+        !Verdict::new(DecisionRule::PerSide, &self.upper_hash, &self.lower_hash, hash).is_accepted()
+    }
+}
+
+/// One simulated sandwich. Amounts of the attacker are in the victim's input token for
+/// the front-run and the back-run output, the victim's in its output token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SandwichOutcome {
+    pub front_run_input: u64,
+    /// Bought by the front-run and sold again by the back-run.
+    pub front_run_output: u64,
+    pub victim_output: u64,
+    /// Output the victim would have received without the attack minus what it received.
+    pub victim_loss: i128,
+    pub back_run_output: u64,
+    /// `back_run_output - front_run_input`.
+    pub attacker_pnl: i128,
+    /// The output is between the victim's slippage boundaries.
+    pub within_slippage: bool,
+    pub distance_to_upper: u32,
+    pub distance_to_lower: u32,
+    /// The commitment rejects the execution, see [`VictimCommitment::rejects`].
+    pub rejected: bool,
+}
+
+impl SandwichOutcome {
+    pub fn is_profitable(&self) -> bool {
+        self.attacker_pnl > 0
+    }
+
+    pub fn is_profitable_undetected(&self) -> bool {
+        self.is_profitable() && !self.rejected
+    }
+}
+
+/// Runs front-run, victim swap and back-run against copies of `pool`. `None` if any of
+/// the three swaps can't be filled.
+pub fn simulate_sandwich<F: Fn(u64) -> LshHash>(
+    pool: &dyn Pool,
+    victim: &VictimTrade,
+    commitment: &VictimCommitment,
+    front_run_input: u64,
+    lsh: F,
+) -> Option<SandwichOutcome> {
+    let mut pool = pool.clone_box();
+
+    let front_run_output = pool.apply_swap(victim.is_swap_x_to_y, front_run_input)?;
+    let victim_output = pool.apply_swap(victim.is_swap_x_to_y, victim.input_amount)?;
+    let back_run_output = pool.apply_swap(!victim.is_swap_x_to_y, front_run_output)?;
+
+    let hash = lsh(victim_output);
+
+    Some(SandwichOutcome {
+        front_run_input,
+        front_run_output,
+        victim_output,
+        victim_loss: commitment.output as i128 - victim_output as i128,
+        back_run_output,
+        attacker_pnl: back_run_output as i128 - front_run_input as i128,
+        within_slippage: (commitment.lower_output..=commitment.upper_output).contains(&victim_output),
+        distance_to_upper: commitment.upper_hash.hamming_distance(&hash),
+        distance_to_lower: commitment.lower_hash.hamming_distance(&hash),
+        rejected: commitment.rejects(&hash),
+    })
+}
+
+/// Result of [`sweep_sandwiches`].
+#[derive(Clone, Debug)]
+pub struct SandwichReport {
+    pub pool: &'static str,
+    pub commitment: VictimCommitment,
+    /// Front-runs the pool could fill, in sweep order.
+    pub outcomes: Vec<SandwichOutcome>,
+}
+
+impl SandwichReport {
+    /// The most profitable sandwich the commitment does not reject.
+    pub fn best_undetected(&self) -> Option<&SandwichOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.is_profitable_undetected()).max_by_key(|outcome| outcome.attacker_pnl)
+    }
+}
+
+impl fmt::Display for SandwichReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |predicate: fn(&SandwichOutcome) -> bool| self.outcomes.iter().filter(|outcome| predicate(outcome)).count();
+
+        writeln!(
+            f,
+            "{}: output {} | upper {} | lower {} | boundary distance {}",
+            self.pool,
+            self.commitment.output,
+            self.commitment.upper_output,
+            self.commitment.lower_output,
+            self.commitment.boundary_distance()
+        )?;
+        writeln!(
+            f,
+            "sandwiches: {} | profitable: {} | rejected: {} | outside slippage: {} | profitable and undetected: {}",
+            self.outcomes.len(),
+            count(SandwichOutcome::is_profitable),
+            count(|outcome| outcome.rejected),
+            count(|outcome| !outcome.within_slippage),
+            count(SandwichOutcome::is_profitable_undetected)
+        )?;
+
+        match self.best_undetected() {
+            Some(best) => writeln!(
+                f,
+                "best undetected: front-run {} | attacker pnl {} | victim loss {}",
+                best.front_run_input, best.attacker_pnl, best.victim_loss
+            ),
+            None => writeln!(f, "best undetected: none"),
+        }
+    }
+}
+
+/// Simulates a sandwich for every front-run size against the same commitment. `None` if
+/// the pool can't fill the victim's trade.
+pub fn sweep_sandwiches<F: Fn(u64) -> LshHash>(
+    pool: &dyn Pool,
+    victim: &VictimTrade,
+    front_run_inputs: impl IntoIterator<Item = u64>,
+    lsh: F,
+) -> Option<SandwichReport> {
+    let commitment = VictimCommitment::new(pool, victim, &lsh)?;
+    let outcomes = front_run_inputs.into_iter().filter_map(|front_run_input| simulate_sandwich(pool, victim, &commitment, front_run_input, &lsh)).collect();
+
+    Some(SandwichReport { pool: pool.name(), commitment, outcomes })
+}
+
+/// Sandwiches at the largest front-run the victim's slippage lets through and around it.
+#[derive(Clone, Debug)]
+pub struct FrontRunProbe {
+    pub pool: &'static str,
+    pub commitment: VictimCommitment,
+    /// See [`max_front_run`].
+    pub max_front_run: u64,
+    /// `(front-run - max_front_run, outcome)`, in front-run order.
+    pub outcomes: Vec<(i128, SandwichOutcome)>,
+}
+
+impl fmt::Display for FrontRunProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: output {} | lower {} | max front-run {} | boundary distance {}",
+            self.pool,
+            self.commitment.output,
+            self.commitment.lower_output,
+            self.max_front_run,
+            self.commitment.boundary_distance()
+        )?;
+        writeln!(f, "{:>14} {:>16} {:>14} {:>14} {:>8} {:>9} {:>9} {:>9}", "offset", "front-run", "victim output", "attacker pnl", "in slip", "to upper", "to lower", "rejected")?;
+
+        for (offset, outcome) in &self.outcomes {
+            writeln!(
+                f,
+                "{:>14} {:>16} {:>14} {:>14} {:>8} {:>9} {:>9} {:>9}",
+                offset,
+                outcome.front_run_input,
+                outcome.victim_output,
+                outcome.attacker_pnl,
+                outcome.within_slippage,
+                outcome.distance_to_upper,
+                outcome.distance_to_lower,
+                outcome.rejected
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the optimal sandwich size with [`max_front_run`] and sandwiches the victim at it,
+/// one unit and 0.1% and 1% inside and outside of it. `None` if the victim's own trade
+/// is already outside its slippage or can't be filled.
+pub fn probe_max_front_run<F: Fn(u64) -> LshHash>(pool: &dyn Pool, victim: &VictimTrade, lsh: F) -> Option<FrontRunProbe> {
+    let commitment = VictimCommitment::new(pool, victim, &lsh)?;
+    let max_front_run = max_front_run(pool, victim.is_swap_x_to_y, victim.input_amount, commitment.lower_output)?;
+
+    let mut front_runs: Vec<u64> = [1, (max_front_run / 1000).max(1), (max_front_run / 100).max(1)]
+        .into_iter()
+        .flat_map(|delta| [max_front_run.saturating_sub(delta), max_front_run.saturating_add(delta)])
+        .chain([max_front_run])
+        .collect();
+    front_runs.sort_unstable();
+    front_runs.dedup();
+
+    let outcomes = front_runs
+        .into_iter()
+        .filter_map(|front_run| {
+            let outcome = simulate_sandwich(pool, victim, &commitment, front_run, &lsh)?;
+            Some((front_run as i128 - max_front_run as i128, outcome))
+        })
+        .collect();
+
+    Some(FrontRunProbe { pool: pool.name(), commitment, max_front_run, outcomes })
+}
@@ -1,9 +1,3 @@
-use num_bigint::BigInt;
-use rand::distr::weighted;
-use solana_poseidon::{hashv, Endianness, Parameters};
-use std::collections::VecDeque;
-use std::fs::OpenOptions;
-use std::io::Write;
 use std::f64::consts::PI;
 
 
@@ -13,10 +7,10 @@ fn main() {
     
     // println!("{}", (PI / 2.0).sin());    
     
-    for i in 0..1000 {
+    for _ in 0..1000 {
         let a = (2.0 * PI * freq * time).sin();
     
         println!("{}", a);    
         time += 0.0001;
     }
-}
\ No newline at end of file
+}
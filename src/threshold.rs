@@ -0,0 +1,204 @@
+//! Smallest perturbation of a trade that moves the hash of its output a target Hamming
+//! distance away from the unperturbed hash, in either swap direction and for moves in
+//! the trader's favor or against it. The experiments' `binary_search_first_hamming_diff`
+//! only handles x -> y front-runs and a target of one bit.
+//!
+//! The distance is not monotone in the perturbation, it rises, falls back and rises again
+//! as projections flip, so a plain bisection can land on any crossing. The search gallops
+//! (1, 2, 4, ...) until a perturbation reaches the target, then splits the bracket into
+//! `subdivisions` parts and keeps the part before the first sample that reaches it, down
+//! to a single unit. A crossing narrower than the samples of its level can still be
+//! missed, more subdivisions make that less likely.
+
+use std::fmt;
+
+use crate::amm::{after_swap, Pool};
+use crate::hash::LshHash;
+
+/// What the search perturbs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Perturbation {
+    /// A front-run of that size goes through before the trade.
+    FrontRun,
+    /// The output itself moves by that much.
+    Output,
+    /// One of the pool's balances grows by that much without a trade, see [`Pool::with_balances`].
+    Reserve,
+}
+
+/// Which way the perturbation moves the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Manipulation {
+    /// More output: a front-run against the trade's direction, a larger output, a larger
+    /// reserve of the output token.
+    InFavor,
+    /// Less output: a front-run in the trade's direction, a smaller output, a larger
+    /// reserve of the input token.
+    Against,
+}
+
+impl fmt::Display for Perturbation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Perturbation::FrontRun => "front_run",
+            Perturbation::Output => "output",
+            Perturbation::Reserve => "reserve",
+        })
+    }
+}
+
+impl fmt::Display for Manipulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Manipulation::InFavor => "in_favor",
+            Manipulation::Against => "against",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThresholdSearch {
+    pub perturbation: Perturbation,
+    pub manipulation: Manipulation,
+    /// Hamming distance from the unperturbed hash to reach, 0 is reached by no perturbation.
+    pub target_distance: u32,
+    /// Largest perturbation tried.
+    pub max_perturbation: u64,
+    /// Parts each bracket is split into, at least 2.
+    pub subdivisions: u64,
+}
+
+impl ThresholdSearch {
+    /// Search up to `u64::MAX` with 8 subdivisions.
+    pub fn new(perturbation: Perturbation, manipulation: Manipulation, target_distance: u32) -> Self {
+        ThresholdSearch { perturbation, manipulation, target_distance, max_perturbation: u64::MAX, subdivisions: 8 }
+    }
+}
+
+/// Result of [`find_threshold`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Threshold {
+    pub perturbation: u64,
+    /// Output of the perturbed trade.
+    pub output: u64,
+    /// Distance of its hash from the unperturbed hash, at least the target.
+    pub distance: u32,
+    /// Perturbations hashed.
+    pub evaluations: usize,
+}
+
+/// Output of the trade under `perturbation` of size `amount`, `None` if the perturbed
+/// trade can't be filled, has no output or the pool can't take the reserve change.
+pub fn perturbed_output(
+    pool: &dyn Pool,
+    is_swap_x_to_y: bool,
+    input_amount: u64,
+    perturbation: Perturbation,
+    manipulation: Manipulation,
+    amount: u64,
+) -> Option<u64> {
+    let in_favor = manipulation == Manipulation::InFavor;
+
+    let output = match perturbation {
+        Perturbation::FrontRun => after_swap(pool, is_swap_x_to_y != in_favor, amount)?.quote_exact_in(is_swap_x_to_y, input_amount)?,
+        Perturbation::Output => {
+            let output = pool.quote_exact_in(is_swap_x_to_y, input_amount)?;
+
+            if in_favor {
+                output.checked_add(amount)?
+            } else {
+                output.checked_sub(amount)?
+            }
+        }
+        Perturbation::Reserve => {
+            let (balance_x, balance_y) = pool.balances();
+
+            // in favor grows the output token's reserve, against the input token's
+            let pool = if is_swap_x_to_y == in_favor {
+                pool.with_balances(balance_x, balance_y.checked_add(amount)?)?
+            } else {
+                pool.with_balances(balance_x.checked_add(amount)?, balance_y)?
+            };
+
+            pool.quote_exact_in(is_swap_x_to_y, input_amount)?
+        }
+    };
+
+    (output > 0).then_some(output)
+}
+
+/// Smallest perturbation reaching `search.target_distance`, see the module docs. `None`
+/// if the pool can't fill the unperturbed trade or no perturbation up to the maximum
+/// (or up to the first one the pool can't take) reaches the target.
+pub fn find_threshold<F: Fn(u64) -> LshHash>(
+    pool: &dyn Pool,
+    is_swap_x_to_y: bool,
+    input_amount: u64,
+    search: &ThresholdSearch,
+    lsh: F,
+) -> Option<Threshold> {
+    let output = pool.quote_exact_in(is_swap_x_to_y, input_amount)?;
+    let base_hash = lsh(output);
+
+    let mut evaluations = 0;
+    let mut evaluate = |amount: u64| {
+        evaluations += 1;
+
+        let output = perturbed_output(pool, is_swap_x_to_y, input_amount, search.perturbation, search.manipulation, amount)?;
+        Some((output, base_hash.hamming_distance(&lsh(output))))
+    };
+
+    let perturbation = first_reaching(
+        |amount| evaluate(amount).map(|(_, distance)| distance),
+        search.target_distance,
+        search.max_perturbation,
+        search.subdivisions,
+    )?;
+    let (output, distance) = if perturbation == 0 { (output, 0) } else { evaluate(perturbation)? };
+
+    Some(Threshold { perturbation, output, distance, evaluations })
+}
+
+/// Smallest amount in `0..=max` whose `distance` is at least `target`, by galloping and
+/// subdividing as in the module docs. Amount 0 is never evaluated, it reaches only a
+/// target of 0. A `None` distance ends the gallop and counts as not reaching otherwise.
+pub fn first_reaching<D: FnMut(u64) -> Option<u32>>(mut distance: D, target: u32, max: u64, subdivisions: u64) -> Option<u64> {
+    assert!(subdivisions >= 2, "subdivisions must be at least 2");
+
+    if target == 0 {
+        return Some(0);
+    }
+
+    let reaches = |distance: Option<u32>| distance.is_some_and(|distance| distance >= target);
+
+    // bracket the first crossing in (low, high]
+    let (mut low, mut high) = (0, 1.min(max));
+    loop {
+        if high == 0 {
+            return None;
+        }
+
+        match distance(high) {
+            None => return None,
+            Some(reached) if reached >= target => break,
+            Some(_) if high == max => return None,
+            Some(_) => (low, high) = (high, high.saturating_mul(2).min(max)),
+        }
+    }
+
+    while high - low > 1 {
+        let step = ((high - low) / subdivisions).max(1);
+        let mut sample = low + step;
+
+        while sample < high {
+            if reaches(distance(sample)) {
+                high = sample;
+                break;
+            }
+            low = sample;
+            sample += step;
+        }
+    }
+
+    Some(high)
+}
@@ -0,0 +1,58 @@
+use lsh::attack::search_attacks;
+use lsh::sandwich::VictimTrade;
+use lsh::vectors::{scheme_by_name, schemes};
+use lsh::{encoder_by_name, hash_value, pool_by_name};
+
+// usage: attack_search [scheme=all] [pool=uniswap_v2] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100] [samples=100]
+//
+// searches front-runs in both directions and back-runs for the most profitable sandwich
+// of an x -> y trade the victim's boundary hashes do not reject, per scheme, and prints
+// the report of each scheme followed by a csv of the worst case per scheme
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let target = name(0, "all");
+    let pool_name = name(1, "uniswap_v2");
+
+    let schemes = match target {
+        "all" => schemes(),
+        _ => vec![scheme_by_name(target).unwrap_or_else(|| panic!("unknown scheme '{}'", target))],
+    };
+    let pool = pool_by_name(pool_name, parse(2, 20_000_000_000), parse(3, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+
+    let victim = VictimTrade { is_swap_x_to_y: true, input_amount: parse(4, 100_000_000), slippage: parse(5, 100) };
+    let samples = parse(6, 100) as usize;
+
+    let mut csv = vec!["scheme,direction,front_run,back_run,attacker_pnl,victim_output,victim_loss,loss_beyond_slippage".to_string()];
+
+    for scheme in schemes {
+        let encoder = encoder_by_name(scheme.encoder).expect("schemes only use known encoders");
+
+        let report = search_attacks(pool.as_ref(), &victim, samples, |output| hash_value(encoder.as_ref(), output, &scheme.config))
+            .expect("the pool cannot fill the victim's trade");
+
+        println!("{}", scheme.name);
+        println!("{}", report);
+
+        csv.push(match report.best() {
+            Some(attack) => format!(
+                "{},{},{},{},{:.0},{},{},{}",
+                scheme.name,
+                if attack.with_victim { "with" } else { "against" },
+                attack.front_run_input,
+                attack.back_run_input,
+                attack.attacker_pnl,
+                attack.victim_output,
+                attack.victim_loss,
+                attack.loss_beyond_slippage(&report.commitment)
+            ),
+            None => format!("{},none,,,,,,", scheme.name),
+        });
+    }
+
+    println!("{}", csv.join("\n"));
+}
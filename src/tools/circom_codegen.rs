@@ -0,0 +1,35 @@
+use lsh::circom::CircuitConfig;
+use std::fs;
+use std::path::Path;
+
+// usage: circom_codegen <config.json> <out_dir>
+//
+// writes <name>.circom for the LSH configuration plus input.json / expected.json, the Rust
+// computed test vector for it (see the circom::codegen docs for the config format)
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.len() < 2 {
+        eprintln!("usage: circom_codegen <config.json> <out_dir>");
+        std::process::exit(2);
+    }
+
+    let json = fs::read_to_string(&args[0]).expect("failed to read the config");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("config is not valid json");
+
+    let dir = Path::new(&args[1]);
+    let written = CircuitConfig::from_json(&json).and_then(|config| {
+        let scenario = config.scenario(&json)?;
+        config.export(dir, &scenario)?;
+
+        Ok(config)
+    });
+
+    match written {
+        Ok(config) => println!("wrote {} with its test vector to {}", config.name, dir.display()),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use lsh::circom::estimate::estimate;
+use lsh::circom::r1cs::{constraint_report, R1cs};
+use lsh::circom::{CircuitConfig, SymbolMap};
+use std::fs;
+use std::path::Path;
+
+// usage:
+//   circom_constraints r1cs <r1cs> [sym]
+//   circom_constraints estimate <config.json> [projection_count...]
+//
+// r1cs counts a compiled circuit (per section with the sym file), estimate computes the
+// count of the generated template for each projection count without compiling
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("r1cs") if args.len() >= 2 => r1cs(&args[1..]),
+        Some("estimate") if args.len() >= 2 => estimate_config(&args[1..]),
+        _ => {
+            eprintln!("usage: circom_constraints r1cs <r1cs> [sym]");
+            eprintln!("       circom_constraints estimate <config.json> [projection_count...]");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
+
+fn r1cs(args: &[String]) {
+    let bytes = fs::read(&args[0]).unwrap_or_else(|err| exit_with(err));
+    let r1cs = R1cs::parse(&bytes).unwrap_or_else(|err| exit_with(err));
+    let symbols = args.get(1).map(|path| SymbolMap::load(Path::new(path)).unwrap_or_else(|err| exit_with(err)));
+
+    let report = constraint_report(&r1cs, symbols.as_ref()).unwrap_or_else(|err| exit_with(err));
+    print!("{}", report);
+}
+
+fn estimate_config(args: &[String]) {
+    let mut config = CircuitConfig::load(Path::new(&args[0])).unwrap_or_else(|err| exit_with(err));
+
+    let projection_counts: Vec<u64> = if args.len() > 1 {
+        args[1..].iter().map(|arg| arg.parse().expect("projection counts must be unsigned integers")).collect()
+    } else {
+        vec![config.projection_count]
+    };
+
+    for projection_count in projection_counts {
+        config.projection_count = projection_count;
+        print!("{}", estimate(&config));
+    }
+}
@@ -0,0 +1,58 @@
+use lsh::circom::Scenario;
+use lsh::projection::{source_by_name, Decoding};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, LshConfig};
+use std::path::Path;
+
+// usage: circom_input <out_dir> <balance_x> <balance_y> <input_amount> <slippage> [x_to_y] [projection_count] [encoder] [salt=test] [decoding] [input_index_offset] [source] [coefficient_bits=64]
+//
+// writes input.json for the circuit and expected.json with the salt commitment and the
+// base/upper/lower hashes it must output. salt is test (salt 0) or a non zero salt with a
+// random blinding, input.json records it
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.len() < 5 {
+        eprintln!("usage: circom_input <out_dir> <balance_x> <balance_y> <input_amount> <slippage> [x_to_y] [projection_count] [encoder] [salt=test] [decoding] [input_index_offset] [source] [coefficient_bits=64]");
+        std::process::exit(2);
+    }
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let encoder_name = name(7, "weighted_nibbles");
+    let decoding_name = name(9, "drop_msb");
+    let source_name = name(11, "poseidon");
+    let coefficient_bits = parse(12, 64) as u32;
+    assert!((2..=64).contains(&coefficient_bits), "coefficient_bits must be between 2 and 64");
+    let salt = match name(8, "test") {
+        "test" => Salt::test(),
+        _ => Salt::new(parse(8, 0), Salt::random().blinding()).unwrap_or_else(|err| panic!("{}", err)),
+    };
+
+    let scenario = Scenario {
+        is_swap_x_to_y: parse(5, 1) != 0,
+        balance_x: parse(1, 0),
+        balance_y: parse(2, 0),
+        input_amount: parse(3, 0),
+        slippage: parse(4, 0),
+        encoder: encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name)),
+        config: LshConfig {
+            input_index_offset: parse(10, 0),
+            source: source_by_name(source_name).unwrap_or_else(|| panic!("unknown source '{}'", source_name)),
+            decoding: Decoding::from_name(decoding_name).unwrap_or_else(|| panic!("unknown decoding '{}'", decoding_name)),
+            ..LshConfig::new(parse(6, 128), salt)
+        },
+        coefficient_bits,
+    };
+
+    let hashes = scenario.evaluate();
+    println!("output {} | upper {} | lower {}", hashes.output, hashes.upper_output, hashes.lower_output);
+    println!("base  {}", hashes.base_hash);
+    println!("upper {}", hashes.upper_hash);
+    println!("lower {}", hashes.lower_hash);
+
+    let dir = Path::new(&args[0]);
+    scenario.export(dir).expect("failed to write the json files");
+    println!("wrote {}", dir.display());
+}
@@ -0,0 +1,56 @@
+use lsh::circom::{check_witness, Backend, Scenario, SymbolMap, Witness};
+use lsh::projection::{source_by_name, Decoding};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, LshConfig};
+use std::path::Path;
+
+// usage: circom_witness <wtns> <sym> [projection_count] [encoder] [decoding] [backend] [input_index_offset] [source] [coefficient_bits=64]
+//
+// reads the trade inputs and the output hashes from a circuit witness and diffs the hashes
+// bit for bit with Rust, exits with 1 if any bit differs. coefficient_bits is the circuit's
+// narrowing from circom_codegen
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.len() < 2 {
+        eprintln!("usage: circom_witness <wtns> <sym> [projection_count] [encoder] [decoding] [backend] [input_index_offset] [source] [coefficient_bits=64]");
+        std::process::exit(2);
+    }
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let encoder_name = name(3, "weighted_nibbles");
+    let decoding_name = name(4, "drop_msb");
+    let backend_name = name(5, "field");
+    let source_name = name(7, "poseidon");
+    let coefficient_bits = parse(8, 64) as u32;
+    assert!((2..=64).contains(&coefficient_bits), "coefficient_bits must be between 2 and 64");
+
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let backend = Backend::from_name(backend_name).unwrap_or_else(|| panic!("unknown backend '{}'", backend_name));
+    let config = LshConfig {
+        input_index_offset: parse(6, 0),
+        source: source_by_name(source_name).unwrap_or_else(|| panic!("unknown source '{}'", source_name)),
+        decoding: Decoding::from_name(decoding_name).unwrap_or_else(|| panic!("unknown decoding '{}'", decoding_name)),
+        ..LshConfig::new(parse(2, 128), Salt::test())
+    };
+
+    let report = Witness::load(Path::new(&args[0]))
+        .and_then(|witness| {
+            let symbols = SymbolMap::load(Path::new(&args[1]))?;
+            let scenario = Scenario::from_witness(&witness, &symbols, encoder, config, coefficient_bits)?;
+
+            check_witness(&scenario, &witness, &symbols, backend)
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        });
+
+    print!("{}", report);
+
+    if !report.is_match() {
+        std::process::exit(1);
+    }
+}
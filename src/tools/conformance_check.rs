@@ -0,0 +1,45 @@
+use lsh::vectors::{check_vectors, load, VectorSpec};
+use std::path::Path;
+
+// usage: conformance_check <expected.json>... [--actual <actual.json>]
+//
+// checks an implementation's output against a golden vector file, without --actual the
+// Rust implementation is checked by regenerating every given file. Exits with 1 on any mismatch
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.is_empty() {
+        eprintln!("usage: conformance_check <expected.json>... [--actual <actual.json>]");
+        std::process::exit(2);
+    }
+
+    let (expected_paths, actual_path) = match args.iter().position(|arg| arg == "--actual") {
+        Some(index) => (&args[..index], Some(args.get(index + 1).expect("--actual needs a file"))),
+        None => (&args[..], None),
+    };
+
+    let mut conforms = true;
+
+    for path in expected_paths {
+        let report = load(Path::new(path))
+            .and_then(|expected| {
+                let actual = match actual_path {
+                    Some(actual_path) => load(Path::new(actual_path))?,
+                    None => VectorSpec::from_json(&expected)?.generate(),
+                };
+
+                check_vectors(&expected, &actual)
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("{}: {}", path, err);
+                std::process::exit(1);
+            });
+
+        print!("{}", report);
+        conforms &= report.is_match();
+    }
+
+    if !conforms {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,46 @@
+use lsh::commitment::{evaluate_rules, CommitmentScheme, Scenario, DEFAULT_RULES};
+use lsh::salt::Salt;
+use lsh::vectors::scheme_by_name;
+use lsh::{encoder_by_name, pool_by_name};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// usage: decision_rules [scheme=v4_split_weight_boundary] [pool=uniswap_v2] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100] [samples=600] [salt_count=4]
+//
+// commits to an x -> y trade with salt_count salts (generated from seeds 0..salt_count, so
+// runs are reproducible) and judges every output of an even
+// sweep from 3x the slippage below the quoted output to 3x above it with each decision
+// rule, then prints the false accept and false reject rates of each rule
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let scheme_name = name(0, "v4_split_weight_boundary");
+    let pool_name = name(1, "uniswap_v2");
+
+    let scheme = scheme_by_name(scheme_name).unwrap_or_else(|| panic!("unknown scheme '{}'", scheme_name));
+    let pool = pool_by_name(pool_name, parse(2, 20_000_000_000), parse(3, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let commitment_scheme = CommitmentScheme::new(encoder_by_name(scheme.encoder).expect("schemes only use known encoders"), scheme.config);
+
+    let scenario = Scenario { pool: pool.as_ref(), is_swap_x_to_y: true, input_amount: parse(4, 100_000_000) };
+    let slippage = parse(5, 100);
+    let samples = parse(6, 600).max(2);
+    let salts: Vec<Salt> = (0..parse(7, 4)).map(|seed| Salt::generate(&mut ChaCha8Rng::seed_from_u64(seed))).collect();
+
+    let output = pool.quote_exact_in(scenario.is_swap_x_to_y, scenario.input_amount).expect("the pool cannot fill the trade");
+    let spread = output as u128 * 3 * slippage as u128 / 10_000;
+    let outputs: Vec<u64> = (0..samples)
+        .map(|k| (output as u128 - spread + 2 * spread * k as u128 / (samples - 1) as u128) as u64)
+        .filter(|&output| output > 0)
+        .collect();
+
+    let evaluations = evaluate_rules(&commitment_scheme, &scenario, slippage, &salts, &outputs, &DEFAULT_RULES).expect("the pool cannot fill the trade");
+
+    println!("{}: output {} | {} outputs x {} salts", scheme.name, output, outputs.len(), salts.len());
+    for evaluation in evaluations {
+        println!("{}", evaluation);
+    }
+}
@@ -0,0 +1,68 @@
+use lsh::projection::divergence::decoding_divergence;
+use lsh::projection::Decoding;
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, LshConfig};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fs;
+
+// usage: decoding_divergence [encoder] [projection_count] [samples] [seed] [inputs=random]
+//
+// hashes a set of values with each decoding and lists the bits that differ. inputs is
+// random (samples swap outputs of every magnitude from seed), a comma separated list of
+// values or a file: file#name takes the column name of a csv with a header or the
+// `name: value` fields of the experiments' csv output, a plain file every unsigned
+// integer in it
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let encoder_name = args.first().map_or("weighted_nibbles", String::as_str);
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+
+    let projection_count = parse(1, 128);
+    let values = match args.get(4).map_or("random", String::as_str) {
+        "random" => {
+            let mut rng = ChaCha8Rng::seed_from_u64(parse(3, 0));
+            (0..parse(2, 200)).map(|_| (rng.random::<u64>() >> rng.random_range(0..64)).max(1)).collect()
+        }
+        list if list.chars().all(|c| c.is_ascii_digit() || c == ',') => {
+            list.split(',').map(|value| value.parse().expect("lists must be comma separated unsigned integers")).collect()
+        }
+        file => read_values(file),
+    };
+    assert!(!values.is_empty(), "no values to hash");
+
+    let inputs: Vec<_> = values.iter().map(|&value| encoder.encode(value)).collect();
+
+    println!("{} values", values.len());
+    let report = decoding_divergence(&inputs, &LshConfig::new(projection_count, Salt::test()), &Decoding::ALL);
+    print!("{}", report);
+}
+
+/// Values of `path#name` or `path`, see the usage. Values that are not unsigned integers
+/// are skipped.
+fn read_values(spec: &str) -> Vec<u64> {
+    let (path, name) = match spec.split_once('#') {
+        Some((path, name)) => (path, Some(name)),
+        None => (spec, None),
+    };
+    let text = fs::read_to_string(path).unwrap_or_else(|err| panic!("{}: {}", path, err));
+    let integers = |text: &str| text.split(|c: char| c == ',' || c == '|' || c == ':' || c.is_whitespace()).filter_map(|token| token.parse().ok()).collect::<Vec<u64>>();
+
+    let Some(name) = name else {
+        return integers(&text);
+    };
+
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').map(str::trim).collect();
+
+    match header.iter().position(|&column| column == name) {
+        Some(column) => lines.filter_map(|line| line.split(',').nth(column)?.trim().parse().ok()).collect(),
+        None => text
+            .lines()
+            .flat_map(|line| line.split('|'))
+            .filter_map(|field| field.split_once(':').filter(|(key, _)| key.trim() == name)?.1.trim().parse().ok())
+            .collect(),
+    }
+}
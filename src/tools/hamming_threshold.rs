@@ -0,0 +1,44 @@
+use lsh::threshold::{find_threshold, Manipulation, Perturbation, ThresholdSearch};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, hash_value, pool_by_name, LshConfig};
+
+// usage: hamming_threshold [pool=constant_product] [projection_count=512] [encoder=weighted_nibbles] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [is_swap_x_to_y=1] [target_distance=1] [subdivisions=8]
+//
+// finds, for every perturbation (front-run, output delta, reserve delta) in the trader's
+// favor and against it, the smallest one whose output hash is at least target_distance
+// bits from the unperturbed hash, and prints them as csv
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let pool_name = name(0, "constant_product");
+    let encoder_name = name(2, "weighted_nibbles");
+
+    let pool = pool_by_name(pool_name, parse(3, 20_000_000_000), parse(4, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let config = LshConfig::new(parse(1, 512), Salt::test());
+
+    let input_amount = parse(5, 100_000_000);
+    let is_swap_x_to_y = parse(6, 1) != 0;
+    let target_distance = parse(7, 1) as u32;
+    let subdivisions = parse(8, 8);
+
+    println!("perturbation,manipulation,amount,output,distance,evaluations");
+
+    for perturbation in [Perturbation::FrontRun, Perturbation::Output, Perturbation::Reserve] {
+        for manipulation in [Manipulation::InFavor, Manipulation::Against] {
+            let search = ThresholdSearch { subdivisions, ..ThresholdSearch::new(perturbation, manipulation, target_distance) };
+
+            match find_threshold(pool.as_ref(), is_swap_x_to_y, input_amount, &search, |output| hash_value(encoder.as_ref(), output, &config)) {
+                Some(threshold) => println!(
+                    "{},{},{},{},{},{}",
+                    perturbation, manipulation, threshold.perturbation, threshold.output, threshold.distance, threshold.evaluations
+                ),
+                None => println!("{},{},,,,", perturbation, manipulation),
+            }
+        }
+    }
+}
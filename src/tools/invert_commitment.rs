@@ -0,0 +1,84 @@
+use lsh::commitment::{CommitmentScheme, Scenario, SlippageCommitment};
+use lsh::field::Fr;
+use lsh::inversion::{invert_commitment, invert_slippage, InversionSearch};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, pool_by_name, slippage_bounds, LshConfig, LshHash, ProjectionMatrix};
+use rand::Rng;
+
+// usage: invert_commitment [pool=uniswap_v2] [projection_count=512] [encoder=weighted_nibbles] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100] [salt_bits=0] [prior=20] [samples=4194304] [beam=4] [max_distance=0] [upper_hash lower_hash]
+//
+// plays the observer against the commitment of an x -> y trade for every salt of a
+// salt_bits bit salt space (0 is the test salt every experiment uses):
+// - seeing the input amount, it quotes the trade and enumerates the slippage
+// - for the salts that reveal one, it also inverts both boundary hashes over the outputs
+//   within prior bps of the quote, as if it only knew the output that roughly
+//
+// the commitment is upper_hash and lower_hash ('0'/'1' strings, e.g. from
+// slippage_commitment), without them the tool commits to the trade itself with slippage
+// and a random salt of the space
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+    let hash = |index: usize| -> LshHash { args[index].parse().unwrap_or_else(|err| panic!("invalid hash: {}", err)) };
+
+    let pool_name = name(0, "uniswap_v2");
+    let encoder_name = name(2, "weighted_nibbles");
+    let (balance_x, balance_y) = (parse(3, 20_000_000_000), parse(4, 20_000_000_000));
+
+    let pool = pool_by_name(pool_name, balance_x, balance_y).unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let scheme = CommitmentScheme::new(encoder, LshConfig::new(parse(1, 512), Salt::test()));
+
+    let scenario = Scenario { pool: pool.as_ref(), is_swap_x_to_y: true, input_amount: parse(5, 100_000_000) };
+    let slippage = parse(6, 100);
+    let salt_bits = parse(7, 0).min(63);
+    let output = pool.quote_exact_in(scenario.is_swap_x_to_y, scenario.input_amount).expect("the pool cannot fill the trade");
+
+    // the salt and slippage behind a given commitment are unknown
+    let (commitment, salt) = match args.len() {
+        0..=12 => {
+            let salt = match salt_bits {
+                0 => Salt::test(),
+                _ => Salt::new(rand::rng().random_range(1..1u64 << salt_bits), Salt::random().blinding()).unwrap(),
+            };
+            (scheme.commit(&scenario, slippage, &salt).expect("the pool cannot fill the trade"), Some(salt))
+        }
+        13 => panic!("a commitment needs both upper_hash and lower_hash"),
+        // the inversion only reads the hashes
+        _ => (SlippageCommitment { upper_hash: hash(12), lower_hash: hash(13), salt_commitment: Fr::from(0u64) }, None),
+    };
+
+    let (prior_high, prior_low) = slippage_bounds(output, parse(8, 20).min(10_000));
+
+    let max_distance = parse(11, 0) as u32;
+    let search = InversionSearch { range: prior_low..=prior_high, samples: parse(9, 1 << 22), beam: parse(10, 4) as usize, max_distance };
+    let candidate_salts = (salt_bits == 0).then(Salt::test).into_iter().chain((1..1u64 << salt_bits).map(|value| Salt::unblinded(value).unwrap()));
+
+    let (mut tried, mut consistent) = (0u64, 0);
+    for candidate in candidate_salts {
+        tried += 1;
+        let slippages = invert_slippage(&commitment, output, 10_000, max_distance, |output| scheme.hash(output, &candidate));
+
+        if !slippages.is_empty() {
+            consistent += 1;
+            let is_real = salt.is_some_and(|salt| salt.value() == candidate.value());
+            println!("salt {}{}", candidate.value(), if is_real { " (the real one)" } else { "" });
+            println!("slippage candidates with the input amount known: {:?}", slippages);
+            print!("{}", invert_commitment(&commitment, &search, |output| scheme.hash(output, &candidate)));
+        }
+
+        // every candidate caches its own matrix, keep only the current one
+        ProjectionMatrix::clear_cache();
+    }
+
+    print!("salts tried {} | consistent {} | quoted output {}", tried, consistent, output);
+    match salt {
+        Some(_) => {
+            let (upper_output, lower_output) = slippage_bounds(output, slippage);
+            println!(" | real window {}..={} ({} bps)", lower_output, upper_output, slippage);
+        }
+        None => println!(),
+    }
+}
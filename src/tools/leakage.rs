@@ -0,0 +1,76 @@
+use lsh::commitment::CommitmentScheme;
+use lsh::encoder::ENCODER_NAMES;
+use lsh::leakage::{estimate_leakage, TradePrior};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, pool_by_name, LshConfig};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// usage: leakage [encoder=all] [projection_counts=64,128,512] [pool=uniswap_v2] [balance_x=20000000000] [balance_y=20000000000] [min_input=1000000] [max_input=1000000000] [slippages=10,50,100,300] [samples=2000] [bins=16] [salt_count=1] [seed=0]
+//
+// draws samples x -> y trades with log-uniform input amounts and uniform slippages (the
+// same trades and salts for every encoder, from seed) and estimates the mutual
+// information between the hashes and the output and slippage per encoder and projection
+// count, then prints a csv of the estimates. "all" skips sequence, it is too slow for
+// outputs this large
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+    let list = |index: usize, default: &'static str| -> Vec<u64> {
+        name(index, default).split(',').map(|value| value.parse().expect("lists must be comma separated unsigned integers")).collect()
+    };
+
+    let target = name(0, "all");
+    let pool_name = name(2, "uniswap_v2");
+
+    let encoders: Vec<&str> = match target {
+        "all" => ENCODER_NAMES.into_iter().filter(|&encoder| encoder != "sequence").collect(),
+        _ => vec![target],
+    };
+    let pool = pool_by_name(pool_name, parse(3, 20_000_000_000), parse(4, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+
+    let prior = TradePrior { is_swap_x_to_y: true, input_range: parse(5, 1_000_000)..=parse(6, 1_000_000_000), slippages: list(7, "10,50,100,300") };
+    let (samples, bins) = (parse(8, 2000) as usize, parse(9, 16) as usize);
+    let seed = parse(11, 0);
+    let salts: Vec<Salt> = (0..parse(10, 1)).map(|index| Salt::generate(&mut ChaCha8Rng::seed_from_u64(seed + index))).collect();
+
+    let mut csv = vec![
+        "encoder,projection_count,output_entropy,bit_leakage_sum,max_bit_leakage,hash_leakage,distinct_hashes,output_decoding_bound,slippage_entropy,boundary_distance_leakage,slippage_decoding_bound".to_string(),
+    ];
+
+    for encoder_name in encoders {
+        for &projection_count in &list(1, "64,128,512") {
+            let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+            let scheme = CommitmentScheme::new(encoder, LshConfig::new(projection_count, Salt::test()));
+
+            let estimate = estimate_leakage(&scheme, pool.as_ref(), &prior, &salts, samples, bins, &mut ChaCha8Rng::seed_from_u64(seed))
+                .expect("the pool cannot fill the prior's trades, or no salt, slippage, bins below 2 or fewer samples than bins");
+
+            println!("{} {}", encoder_name, projection_count);
+            println!("{}", estimate);
+
+            csv.push(format!(
+                "{},{},{:.4},{:.4},{:.4},{:.4},{},{:.4},{:.4},{:.4},{:.4}",
+                encoder_name,
+                projection_count,
+                estimate.output_entropy,
+                estimate.bit_leakage_sum(),
+                estimate.max_bit_leakage(),
+                estimate.hash_leakage,
+                estimate.distinct_hashes,
+                estimate.output_decoding_bound,
+                estimate.slippage_entropy,
+                estimate.boundary_distance_leakage,
+                estimate.slippage_decoding_bound
+            ));
+        }
+    }
+
+    println!();
+    for line in csv {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,65 @@
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use lsh::gadget::{constraint_count, is_satisfied, prove, setup, verify, LshCircuit};
+use lsh::projection::Decoding;
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, generate_lsh_rust, Features, LshConfig};
+use std::time::Instant;
+
+// usage: lsh_groth16 [projection_count] [encoder] [value] [reference_value] [max_distance] [salt=random] [decoding]
+//
+// proves with Groth16 that the hash of value is within max_distance bits of the hash of
+// reference_value, and prints constraint count, setup, proving and verification times.
+// salt is random, test (salt 0) or a non zero salt with a random blinding, only its
+// commitment is public
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let encoder_name = args.get(1).map_or("weighted_nibbles", String::as_str);
+    let decoding_name = args.get(6).map_or("twos_complement", String::as_str);
+
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let salt = match args.get(5).map_or("random", String::as_str) {
+        "random" => Salt::random(),
+        "test" => Salt::test(),
+        _ => Salt::new(parse(5, 0), Salt::random().blinding()).unwrap_or_else(|err| panic!("{}", err)),
+    };
+    let config = LshConfig {
+        decoding: Decoding::from_name(decoding_name).unwrap_or_else(|| panic!("unknown decoding '{}'", decoding_name)),
+        ..LshConfig::new(parse(0, 64), salt)
+    };
+    let value = parse(2, 1_000_000);
+    let reference_value = parse(3, 1_000_100);
+    let max_distance = parse(4, 4);
+
+    let integer = |value: u64| match encoder.encode(value) {
+        Features::Integer(features) => features,
+        Features::Float(_) => panic!("{} produces float features, the circuit needs integers", encoder_name),
+    };
+    let features = integer(value);
+    let reference = generate_lsh_rust(&integer(reference_value), &config);
+
+    let distance = generate_lsh_rust(&features, &config).hamming_distance(&reference);
+    println!("rust distance {} | max distance {} | statement holds: {}", distance, max_distance, distance as u64 <= max_distance);
+
+    let (constraints, variables) = constraint_count(config, features.len()).expect("failed to synthesize");
+    println!("constraints: {} | variables: {}", constraints, variables);
+
+    let circuit = LshCircuit::new(config, &salt, features.clone(), reference.clone(), max_distance);
+    println!("satisfied: {}", is_satisfied(circuit.clone()).expect("failed to synthesize"));
+
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let start = Instant::now();
+    let (proving_key, verifying_key) = setup(config, features.len(), max_distance, &mut rng).expect("setup failed");
+    println!("setup: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let proof = prove(&proving_key, circuit, &mut rng).expect("proving failed");
+    println!("prove: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let is_valid = verify(&verifying_key, salt.commitment(), &reference, &proof).expect("verification failed");
+    println!("verify: {:?} | valid: {}", start.elapsed(), is_valid);
+}
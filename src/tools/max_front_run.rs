@@ -0,0 +1,30 @@
+use lsh::sandwich::{probe_max_front_run, VictimTrade};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, hash_value, pool_by_name, LshConfig};
+
+// usage: max_front_run [pool=uniswap_v2] [projection_count=512] [encoder=weighted_nibbles] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100]
+//
+// computes the largest front-run an x -> y trade's slippage lets through (closed form for
+// constant product pools, numerically otherwise) and sandwiches the trade at it and just
+// inside and outside of it
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let pool_name = name(0, "uniswap_v2");
+    let encoder_name = name(2, "weighted_nibbles");
+
+    let pool = pool_by_name(pool_name, parse(3, 20_000_000_000), parse(4, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let config = LshConfig::new(parse(1, 512), Salt::test());
+
+    let victim = VictimTrade { is_swap_x_to_y: true, input_amount: parse(5, 100_000_000), slippage: parse(6, 100) };
+
+    let probe = probe_max_front_run(pool.as_ref(), &victim, |output| hash_value(encoder.as_ref(), output, &config))
+        .expect("the victim's trade is already outside its slippage or can't be filled");
+
+    print!("{}", probe);
+}
@@ -0,0 +1,70 @@
+use lsh::amm::after_swap;
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, get_pool_boundary_hashes, get_pool_hash, hash_value, pool_by_name, LshConfig};
+
+// usage: pool_front_run [pool=clmm] [projection_count=512] [encoder=weighted_nibbles] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100] [front_run_base=100000000] [steps=20]
+//
+// the v6 front-run experiment against any pool model: hashes the boundaries of an x -> y
+// trade, then front-runs it with growing amounts in both directions and prints, as csv,
+// the tick the front-run leaves (tick based pools), the victim output and the Hamming
+// distances of its hash to both boundaries
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let pool_name = name(0, "clmm");
+    let encoder_name = name(2, "weighted_nibbles");
+    let (balance_x, balance_y) = (parse(3, 20_000_000_000), parse(4, 20_000_000_000));
+    let input_amount = parse(5, 100_000_000);
+    let slippage = parse(6, 100);
+    let front_run_base = parse(7, 100_000_000);
+
+    let pool = pool_by_name(pool_name, balance_x, balance_y).unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let config = LshConfig::new(parse(1, 512), Salt::test());
+    let lsh = |output: u64| hash_value(encoder.as_ref(), output, &config);
+
+    // the victim trades x to y
+    let is_swap_x_to_y = true;
+
+    let ([upper_hash, lower_hash], upper_output, lower_output, output) =
+        get_pool_boundary_hashes(pool.as_ref(), is_swap_x_to_y, input_amount, slippage, lsh).expect("the pool cannot fill the trade");
+    let boundary_distance = upper_hash.hamming_distance(&lower_hash);
+
+    println!("# {} | spot {} | tick {:?}", pool_name, pool.spot_price(), pool.tick());
+    println!("# output {} | upper {} | lower {} | boundary distance {}", output, upper_output, lower_output, boundary_distance);
+    println!("front_run,direction,tick,output,output_change,to_upper,to_lower,outside_slippage,detected");
+
+    for step in 1..=parse(8, 20) {
+        let front_run = front_run_base * step;
+
+        // trading y in makes y cheaper and the victim better off, trading x in worse off
+        for (direction, is_front_run_x_to_y) in [("better", false), ("worse", true)] {
+            let Some((hash, victim_output, tick)) = after_swap(pool.as_ref(), is_front_run_x_to_y, front_run).and_then(|front_run_pool| {
+                let (hash, victim_output) = get_pool_hash(front_run_pool.as_ref(), is_swap_x_to_y, input_amount, lsh)?;
+                Some((hash, victim_output, front_run_pool.tick()))
+            }) else {
+                println!("{},{},,,,,,,", front_run, direction);
+                continue;
+            };
+
+            let to_upper = upper_hash.hamming_distance(&hash);
+            let to_lower = lower_hash.hamming_distance(&hash);
+
+            println!(
+                "{},{},{},{},{},{},{},{},{}",
+                front_run,
+                direction,
+                tick.map_or(String::new(), |tick| tick.to_string()),
+                victim_output,
+                victim_output as f64 / output as f64 - 1.0,
+                to_upper,
+                to_lower,
+                victim_output > upper_output || victim_output < lower_output,
+                to_upper >= boundary_distance || to_lower >= boundary_distance,
+            );
+        }
+    }
+}
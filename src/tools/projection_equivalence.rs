@@ -0,0 +1,22 @@
+use lsh::salt::Salt;
+use lsh::{check_equivalence, ChaCha8, Poseidon};
+
+// usage: projection_equivalence [projection_count] [feature_count] [pairs] [salt=test]
+fn main() {
+    let args: Vec<u64> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("arguments must be unsigned integers"))
+        .collect();
+
+    let projection_count = args.first().copied().unwrap_or(256);
+    let feature_count = args.get(1).copied().unwrap_or(16);
+    let pairs = args.get(2).copied().unwrap_or(200) as usize;
+    let salt = args.get(3).map_or(Salt::test(), |&salt| Salt::unblinded(salt).unwrap_or_else(|err| panic!("{}", err)));
+
+    let report = check_equivalence(&Poseidon, &ChaCha8, salt, projection_count, feature_count, pairs);
+    println!("{}", report);
+
+    if !report.is_equivalent() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,88 @@
+use lsh::projection::{source_by_name, Decoding};
+use lsh::salt::Salt;
+use lsh::{LshConfig, ProjectionMatrix};
+use std::path::{Path, PathBuf};
+
+// usage:
+//   projection_matrix generate <file or dir> <projection_count> <feature_count> [salt=test] [input_index_offset] [source] [decoding]
+//   projection_matrix verify <file> [spot_checks]
+//
+// a directory gets the default file name, so it can be used as LSH_MATRIX_DIR
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("generate") if args.len() >= 4 => generate(&args[1..]),
+        Some("verify") if args.len() >= 2 => verify(&args[1..]),
+        _ => {
+            eprintln!("usage: projection_matrix generate <file or dir> <projection_count> <feature_count> [salt=test] [input_index_offset] [source] [decoding]");
+            eprintln!("       projection_matrix verify <file> [spot_checks]");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn parse(arg: Option<&String>, default: u64) -> u64 {
+    arg.map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"))
+}
+
+fn generate(args: &[String]) {
+    let source_name = args.get(5).map_or("poseidon", String::as_str);
+    let decoding_name = args.get(6).map_or("twos_complement", String::as_str);
+    let salt = match args.get(3).map_or("test", String::as_str) {
+        "test" => Salt::test(),
+        _ => Salt::unblinded(parse(args.get(3), 0)).unwrap_or_else(|err| panic!("{}", err)),
+    };
+    let config = LshConfig {
+        input_index_offset: parse(args.get(4), 0),
+        source: source_by_name(source_name).unwrap_or_else(|| panic!("unknown source '{}'", source_name)),
+        decoding: Decoding::from_name(decoding_name).unwrap_or_else(|| panic!("unknown decoding '{}'", decoding_name)),
+        ..LshConfig::new(parse(args.get(1), 0), salt)
+    };
+    let feature_count = parse(args.get(2), 0);
+
+    let mut path = PathBuf::from(&args[0]);
+    if path.is_dir() {
+        path.push(lsh::matrix::file::file_name(&config, feature_count));
+    }
+
+    println!("generating {} x {} {} projections", config.projection_count, feature_count, source_name);
+    let matrix = ProjectionMatrix::new(&config, feature_count);
+    matrix.save(&path).expect("failed to write the matrix");
+
+    // read it back so a bad write fails here and not in the experiment run
+    ProjectionMatrix::load_for(&path, &config, feature_count).expect("written matrix does not verify");
+    println!("wrote {}", path.display());
+}
+
+fn verify(args: &[String]) {
+    let path = Path::new(&args[0]);
+    let spot_checks = parse(args.get(1), 64);
+
+    let matrix = match ProjectionMatrix::load(path) {
+        Ok(matrix) => matrix,
+        Err(err) => {
+            eprintln!("{}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    };
+
+    println!(
+        "{}: source {} | salt {} | {} x {} | input index offset {} | decoding {}",
+        path.display(),
+        matrix.source().name(),
+        matrix.salt(),
+        matrix.projection_count(),
+        matrix.feature_count(),
+        matrix.input_index_offset(),
+        matrix.decoding().name(),
+    );
+
+    // the checksum only proves the file is intact, re-derive a few entries to prove it is right
+    if let Some((i, j)) = matrix.spot_check(spot_checks) {
+        eprintln!("projection {} feature {} does not match its source", i, j);
+        std::process::exit(1);
+    }
+
+    println!("ok");
+}
@@ -0,0 +1,49 @@
+use lsh::sandwich::{sweep_sandwiches, VictimTrade};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, hash_value, pool_by_name, LshConfig};
+
+// usage: sandwich [pool=uniswap_v2] [projection_count=512] [encoder=weighted_nibbles] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100] [front_run_base=100000000] [steps=50]
+//
+// sandwiches an x -> y trade with growing front-runs (front-run, victim, back-run, pool
+// fees included) and prints, as csv, attacker pnl, victim loss and whether the victim's
+// boundary hashes reject the execution, then a summary with the best undetected sandwich
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let pool_name = name(0, "uniswap_v2");
+    let encoder_name = name(2, "weighted_nibbles");
+
+    let pool = pool_by_name(pool_name, parse(3, 20_000_000_000), parse(4, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let config = LshConfig::new(parse(1, 512), Salt::test());
+
+    let victim = VictimTrade { is_swap_x_to_y: true, input_amount: parse(5, 100_000_000), slippage: parse(6, 100) };
+    let front_run_base = parse(7, 100_000_000);
+
+    let report = sweep_sandwiches(pool.as_ref(), &victim, (1..=parse(8, 50)).map(|step| front_run_base * step), |output| {
+        hash_value(encoder.as_ref(), output, &config)
+    })
+    .expect("the pool cannot fill the victim's trade");
+
+    println!("front_run,victim_output,victim_loss,back_run_output,attacker_pnl,within_slippage,to_upper,to_lower,rejected");
+    for outcome in &report.outcomes {
+        println!(
+            "{},{},{},{},{},{},{},{},{}",
+            outcome.front_run_input,
+            outcome.victim_output,
+            outcome.victim_loss,
+            outcome.back_run_output,
+            outcome.attacker_pnl,
+            outcome.within_slippage,
+            outcome.distance_to_upper,
+            outcome.distance_to_lower,
+            outcome.rejected
+        );
+    }
+
+    print!("{}", report);
+}
@@ -0,0 +1,48 @@
+use lsh::commitment::{CommitmentScheme, Scenario};
+use lsh::salt::Salt;
+use lsh::{encoder_by_name, pool_by_name, LshConfig};
+
+// usage: slippage_commitment [pool=uniswap_v2] [projection_count=512] [encoder=weighted_nibbles] [balance_x=20000000000] [balance_y=20000000000] [input_amount=100000000] [slippage=100] [salt=random] [executed_output]...
+//
+// commits to the slippage boundaries of an x -> y trade and verifies each executed output
+// against the commitment, by default the quoted output and every 0.25% step from 2% above
+// to 2% below it. salt is random, test (salt 0) or a non zero salt with a random blinding
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let pool_name = name(0, "uniswap_v2");
+    let encoder_name = name(2, "weighted_nibbles");
+
+    let pool = pool_by_name(pool_name, parse(3, 20_000_000_000), parse(4, 20_000_000_000))
+        .unwrap_or_else(|| panic!("unknown pool '{}' or unusable balances", pool_name));
+    let encoder = encoder_by_name(encoder_name).unwrap_or_else(|| panic!("unknown encoder '{}'", encoder_name));
+    let scheme = CommitmentScheme::new(encoder, LshConfig::new(parse(1, 512), Salt::test()));
+
+    let scenario = Scenario { pool: pool.as_ref(), is_swap_x_to_y: true, input_amount: parse(5, 100_000_000) };
+    let salt = match name(7, "random") {
+        "random" => Salt::random(),
+        "test" => Salt::test(),
+        _ => Salt::new(parse(7, 0), Salt::random().blinding()).unwrap_or_else(|err| panic!("{}", err)),
+    };
+
+    let commitment = scheme.commit(&scenario, parse(6, 100), &salt).expect("the pool cannot fill the trade");
+    let output = pool.quote_exact_in(scenario.is_swap_x_to_y, scenario.input_amount).expect("the pool cannot fill the trade");
+
+    let executed_outputs: Vec<u64> = if args.len() > 8 {
+        (8..args.len()).map(|index| parse(index, 0)).collect()
+    } else {
+        (-8..=8).map(|step: i128| (output as i128 * (10_000 - 25 * step) / 10_000) as u64).collect()
+    };
+
+    println!("quoted output {} | salt commitment {} | boundary distance {}", output, commitment.salt_commitment, commitment.boundary_distance());
+    println!("upper hash {}", commitment.upper_hash);
+    println!("lower hash {}", commitment.lower_hash);
+
+    let verdicts = scheme.verify_batch(executed_outputs.iter().map(|&executed_output| (&commitment, executed_output, &salt)));
+    for (executed_output, verdict) in executed_outputs.iter().zip(&verdicts) {
+        println!("{}: {}", executed_output, verdict);
+    }
+}
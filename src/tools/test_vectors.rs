@@ -0,0 +1,59 @@
+use lsh::projection::{source_by_name, Decoding};
+use lsh::salt::Salt;
+use lsh::vectors::{scheme_by_name, schemes, default_values, VectorSpec, DEFAULT_SALTS};
+use lsh::{encoder_by_name, LshConfig};
+use std::path::Path;
+
+// usage: test_vectors <out_dir> [projection_count=8] [scheme_or_encoder=all] [decoding] [input_index_offset] [source]
+//
+// writes <out_dir>/<name>.json golden vectors. "all" writes every scheme, a scheme name
+// writes that scheme with at most projection_count projections, an encoder name writes
+// a set for that encoder with the given config
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.is_empty() {
+        eprintln!("usage: test_vectors <out_dir> [projection_count=8] [scheme_or_encoder=all] [decoding] [input_index_offset] [source]");
+        std::process::exit(2);
+    }
+
+    let parse = |index: usize, default: u64| args.get(index).map_or(default, |arg| arg.parse().expect("arguments must be unsigned integers"));
+    let name = |index: usize, default: &'static str| args.get(index).map_or(default, String::as_str);
+
+    let projection_count = parse(1, 8);
+    let target = name(2, "all");
+
+    let specs: Vec<VectorSpec> = if target == "all" {
+        schemes().iter().map(|scheme| VectorSpec::for_scheme(scheme, projection_count)).collect()
+    } else if let Some(scheme) = scheme_by_name(target) {
+        vec![VectorSpec::for_scheme(&scheme, projection_count)]
+    } else {
+        let encoder = encoder_by_name(target).unwrap_or_else(|| panic!("'{}' is neither a scheme nor an encoder", target));
+        let decoding_name = name(3, "twos_complement");
+        let source_name = name(5, "poseidon");
+
+        let config = LshConfig {
+            input_index_offset: parse(4, 0),
+            source: source_by_name(source_name).unwrap_or_else(|| panic!("unknown source '{}'", source_name)),
+            decoding: Decoding::from_name(decoding_name).unwrap_or_else(|| panic!("unknown decoding '{}'", decoding_name)),
+            ..LshConfig::new(projection_count, Salt::test())
+        };
+
+        vec![VectorSpec {
+            name: format!("{}_{}_{}_{}", target, config.source.name(), config.decoding.name(), projection_count),
+            encoder,
+            config,
+            salts: DEFAULT_SALTS.to_vec(),
+            values: default_values(target).to_vec(),
+        }]
+    };
+
+    let dir = Path::new(&args[0]);
+
+    for spec in specs {
+        spec.export(dir).expect("failed to write the vector file");
+        println!("{}: {} projections x {} features, {} salts x {} values", spec.name, spec.config.projection_count, spec.encoder.dimension(), spec.salts.len(), spec.values.len());
+    }
+
+    println!("wrote {}", dir.display());
+}
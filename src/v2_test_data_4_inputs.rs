@@ -1,119 +1,18 @@
+use lsh::salt::Salt;
 use itertools::izip;
-use solana_poseidon::{hashv, Endianness, Parameters};
+use lsh::{fake_trade_to_x, fake_trade_to_y, generate_lsh_rust, swap, LshConfig, LshHash};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 
-fn hamming_distance(a: u64, b: u64) -> u32 {
-    (a ^ b).count_ones()
-}
-
-fn hamming_distance_128(a: u64, b: u64) -> u32 {
-    (a ^ b).count_ones()
-}
-
-fn hamming_distance_string(a: &str, b: &str) -> u32 {
-    a.chars()
-        .zip(b.chars())
-        .filter(|(char_a, char_b)| char_a != char_b)
-        .count() as u32
-}
-
-fn normalize_vector(v: &Vec<f64>) -> Vec<f64> {
-    let norm: f64 = v.iter().map(|&x| x * x).sum::<f64>().sqrt();
-
-    if norm == 0.0 {
-        v.clone() // Avoid division by zero
-    } else {
-        v.iter().map(|&x| x / norm).collect()
-    }
-}
-
-fn split_u64_le(value: u64) -> [u8; 8] {
-    value.to_le_bytes()
-}
-
-fn generate_lsh_rust(inputs: &[u64; 4]) -> String {
-    let mut hash_res: String = "".to_string();
-
-    let salt = 0;
-
-    let salt_bytes = u64::to_le_bytes(salt);
-
-    for i in 0..512 {
-        let index_bytes = u64::to_le_bytes(i);
-
-        let mut input_index = 0;
-        let mut final_sum = 0;
-        for &input in inputs.iter() {
-            let input_index_bytes = u64::to_le_bytes(input_index);
-            input_index += 1;
-
-            let dim0: &[&[u8]] = &[&salt_bytes, &index_bytes, &input_index_bytes];
-
-            let pos_hash0 = hashv(Parameters::Bn254X5, Endianness::LittleEndian, &dim0).unwrap();
-            let pos_hash_bytes0 = pos_hash0.to_bytes();
-
-            let mut array0 = [0u8; 8];
-            array0.copy_from_slice(&pos_hash_bytes0[..8]);
-
-            let projection = i64::from_le_bytes(array0);
+fn get_hash(is_swap_x_to_y: bool, balance_x: u64, balance_y: u64, input_amount: u64) -> LshHash {
+    let (new_balance_x, new_balance_y, _) = swap(is_swap_x_to_y, balance_x, balance_y, input_amount);
 
-            let mult0 = input as i128 * projection as i128;
-            final_sum += mult0;
-        }
-
-        if final_sum < 0 {
-            hash_res.push('1');
-        } else {
-            hash_res.push('0');
-        }
-    }
-
-    hash_res
-}
-
-fn get_hash(is_swap_x_to_y: bool, balance_x: u64, balance_y: u64, input_amount: u64) -> String {
-    let k = balance_x as u128 * balance_y as u128;
-
-    let new_balance_x;
-    let new_balance_y;
-    let output;
-    if (is_swap_x_to_y) {
-        new_balance_x = balance_x + input_amount;
-        new_balance_y = (k / new_balance_x as u128) as u64;
-        output = balance_y - new_balance_y;
-    } else {
-        new_balance_y = balance_y + input_amount;
-        new_balance_x = (k / new_balance_y as u128) as u64;
-        output = balance_x - new_balance_x;
-    }
-
-    // salt is 0 for now
     let input_vector: [u64; 4] = [balance_x, balance_y, new_balance_x, new_balance_y]; // Example input
-    let lsh_hash = generate_lsh_rust(&input_vector);
-    lsh_hash
-}
-
-fn fake_trade_to_y(balance_x: u64, balance_y: u64, input_amount: u64) -> (u64, u64) {
-    let k = balance_x as u128 * balance_y as u128;
-
-    let new_balance_x = balance_x + input_amount;
-    let new_balance_y = (k / new_balance_x as u128) as u64;
-
-    (new_balance_x, new_balance_y)
-}
-
-fn fake_trade_to_x(balance_x: u64, balance_y: u64, input_amount: u64) -> (u64, u64) {
-    let k = balance_x as u128 * balance_y as u128;
-
-    let new_balance_y = balance_y + input_amount;
-    let new_balance_x = (k / new_balance_y as u128) as u64;
-
-    (new_balance_x, new_balance_y)
+    generate_lsh_rust(&input_vector, &LshConfig::new(512, Salt::test()))
 }
 
 fn binary_search_first_hamming_diff(
-    base_hash: &str,
+    base_hash: &LshHash,
     base_reserve_x: u64,
     base_reserve_y: u64,
     input_amount: u64,
@@ -160,7 +59,7 @@ fn binary_search_first_hamming_diff(
             input_amount,
         );
 
-        let hamming_distance = hamming_distance_string(&base_hash, &new_hash);
+        let hamming_distance = base_hash.hamming_distance(&new_hash);
         println!("hamming_distance: {}", hamming_distance);
 
         if hamming_distance != 0 {
@@ -193,7 +92,7 @@ fn binary_search_first_hamming_diff(
 
     println!("found: {}, {}", first_hamm_reserve_x, first_hamm_reserve_y);
 
-    return (first_hamm_reserve_x, first_hamm_reserve_y, front_run_input);
+    (first_hamm_reserve_x, first_hamm_reserve_y, front_run_input)
 }
 
 fn main() {
@@ -213,7 +112,7 @@ fn main() {
     writeln!(file, "amount_in,reserve_in,reserve_out,in_favor_amount,first_reserve_in_hamming_in_favor,first_reserve_out_hamming_in_favor,against_amount,first_reserve_in_hamming_against,first_reserve_out_hamming_against").unwrap();
 
     for (index, line) in reader.lines().enumerate() {
-        if (index == 0) {
+        if index == 0 {
             // skip headers
             continue;
         }
@@ -230,7 +129,7 @@ fn main() {
             Some(reserve_in),
             Some(reserve_out),
         ) = (
-            values.get(0),
+            values.first(),
             values.get(1),
             values.get(2),
             values.get(3),
@@ -242,10 +141,10 @@ fn main() {
                 .parse()
                 .expect("Unable to parse reserve_in");
             let dec_from: f64 = dec_from.parse().expect("Unable to parse reserve_in");
-            let dec_to: f64 = dec_to.parse().expect("Unable to parse reserve_in");
+            let _dec_to: f64 = dec_to.parse().expect("Unable to parse reserve_in");
 
             let amount_in: f64 = amount_in.parse().expect("Unable to parse reserve_in");
-            let amount_out: f64 = amount_out.parse().expect("Unable to parse reserve_in");
+            let _amount_out: f64 = amount_out.parse().expect("Unable to parse reserve_in");
             let reserve_in: f64 = reserve_in.parse().expect("Unable to parse reserve_in");
             let reserve_out: f64 = reserve_out.parse().expect("Unable to parse reserve_out");
 
@@ -0,0 +1,513 @@
+//! Versioned golden test vectors, so the Rust, circom and frontend implementations can
+//! be checked against the same numbers instead of against each other's logs.
+//!
+//! A vector file holds one [`VectorSpec`]: the encoder and LSH config, then per salt the
+//! Poseidon output, raw projection and decoded coefficient of every (projection, feature)
+//! pair, and per input value the features, the projection sums and the hash:
+//!
+//! ```json
+//! {
+//!   "format": "lsh-test-vectors", "version": 1, "name": "v4_split_weight_boundary",
+//!   "encoder": "weighted_nibbles", "source": "poseidon", "decoding": "twos_complement",
+//!   "input_index_offset": 5000, "projection_count": 8, "feature_count": 16,
+//!   "groups": [{
+//!     "salt": "0",
+//!     "poseidon": [["<field element>", ...], ...],
+//!     "raw": [["<i64>", ...], ...],
+//!     "coefficients": [["<i64>", ...], ...],
+//!     "cases": [{ "value": "1000000", "features": [...], "sums": [...], "hash": "0110..." }]
+//!   }]
+//! }
+//! ```
+//!
+//! Matrices are indexed `[projection][feature]`. Integers are decimal strings since most
+//! exceed 2^53, float features and sums are JSON numbers. `poseidon` is null for other
+//! sources. Sums are the exact signed dot products (float sums are accumulated in feature
+//! order), a negative sum sets the hash bit.
+//!
+//! Rows are independent, so a set with fewer projections than its scheme still pins the
+//! first bits of the scheme's hash. The pinned sets in `vectors/v1` cap the projection
+//! count to keep the files small.
+
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::{BigInt, BigUint};
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::encoder::{encoder_by_name, FeatureEncoder, Features};
+use crate::field::{field_partial_sums, to_signed, Fr};
+use crate::lsh::{projection_sum_f64, LshConfig};
+use crate::matrix::ProjectionMatrix;
+use crate::projection::{poseidon_projection_bytes, source_by_name, Decoding};
+use crate::salt::Salt;
+
+pub const FORMAT: &str = "lsh-test-vectors";
+/// Bumped whenever the file layout or the meaning of a value changes.
+pub const VERSION: u64 = 1;
+
+/// The experiments' salt 0, then small, typical, MSB only and all ones salts.
+pub const DEFAULT_SALTS: [u64; 5] = [0, 1, 0x5eed_0000_1234_abcd, 1 << 63, u64::MAX];
+/// Zero, small, typical and extreme amounts.
+pub const DEFAULT_VALUES: [u64; 8] = [0, 1, 255, 4_999, 1_000_000, 1_999_999_999, 1 << 40, u64::MAX];
+/// `checkpoints` divides 0 by 0 for a zero amount.
+pub const POSITIVE_VALUES: [u64; 7] = [1, 255, 4_999, 1_000_000, 1_999_999_999, 1 << 40, u64::MAX];
+/// `sequence` only takes 1 to `u32::MAX`.
+pub const SEQUENCE_VALUES: [u64; 6] = [1, 255, 4_999, 1_000_000, 1_999_999_999, u32::MAX as u64];
+
+/// Matrix values of a group, in file order.
+pub const MATRIX_KINDS: [&str; 3] = ["poseidon", "raw", "coefficients"];
+/// Values compared by [`check_vectors`], in report order.
+pub const KINDS: [&str; 6] = ["poseidon", "raw", "coefficients", "features", "sums", "hash"];
+
+/// Mismatches listed in a [`ConformanceReport`], the counts cover all of them.
+pub const MAX_LISTED: usize = 20;
+
+/// The encoder and config one of the experiment binaries hashes with.
+#[derive(Clone, Copy, Debug)]
+pub struct Scheme {
+    pub name: &'static str,
+    pub encoder: &'static str,
+    pub config: LshConfig,
+}
+
+/// Every scheme a binary builds from an encoder and an [`LshConfig`]. Not covered:
+///
+/// - `main_*`, `v2` and `v3` build their features by hand
+/// - `v4_split_split_boundary` pushes a bit per prefix of every nibble's dot product
+///   (136 bits from 16 nibbles), not one sign per projection
+/// - `v4_split_weight_boundary_norm` scales projections to `p / i64::MAX * 2 - 1`, a
+///   range of [-3, 1] no [`LshConfig`] hasher uses
+pub fn schemes() -> Vec<Scheme> {
+    use crate::projection::ChaCha8;
+
+    let scheme = |name, encoder, config| Scheme { name, encoder, config };
+
+    vec![
+        scheme("v4_split_binary_boundary", "bits", LshConfig { input_index_offset: 1000, ..LshConfig::new(64, Salt::test()) }),
+        scheme("v4_split_binary_wma_boundary", "bit_wma", LshConfig::new(512, Salt::test())),
+        scheme("v4_split_multi_boundary", "averaged_weighted_nibbles", LshConfig { input_index_offset: 5000, ..LshConfig::new(512, Salt::test()) }),
+        scheme("v4_split_weight_boundary", "weighted_nibbles", LshConfig { input_index_offset: 5000, ..LshConfig::new(128, Salt::test()) }),
+        scheme("v5_back_to_monke", "normalized", LshConfig::new(8192, Salt::test())),
+        scheme("v5_back_to_monke_rand", "normalized", LshConfig { source: &ChaCha8, ..LshConfig::new(8192, Salt::test()) }),
+        scheme("v5_split_binary_sine_boundary", "sine", LshConfig::new(512, Salt::test())),
+        scheme("v6_checkpoints", "checkpoints", LshConfig::new(2048, Salt::test())),
+        scheme("v6_moving_weight_boundary", "shifted_nibbles", LshConfig::new(128, Salt::test())),
+        scheme("v6_sequence", "sequence", LshConfig::new(4096, Salt::test())),
+    ]
+}
+
+pub fn scheme_by_name(name: &str) -> Option<Scheme> {
+    schemes().into_iter().find(|scheme| scheme.name == name)
+}
+
+/// [`DEFAULT_VALUES`], or the subset an encoder with a narrower domain accepts.
+pub fn default_values(encoder: &str) -> &'static [u64] {
+    match encoder {
+        "checkpoints" => &POSITIVE_VALUES,
+        "sequence" => &SEQUENCE_VALUES,
+        _ => &DEFAULT_VALUES,
+    }
+}
+
+/// Error from reading, parsing or checking a vector file.
+#[derive(Debug)]
+pub enum VectorError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Not a vector file, or a version this build does not know.
+    Format(String),
+    /// Missing or mistyped field.
+    BadField(&'static str),
+    UnknownEncoder(String),
+    UnknownSource(String),
+    UnknownDecoding(String),
+    /// Both files are vectors, but not of the same set.
+    DifferentSet { field: String, expected: Value, actual: Value },
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VectorError::Io(err) => write!(f, "io error: {}", err),
+            VectorError::Json(err) => write!(f, "invalid json: {}", err),
+            VectorError::Format(found) => write!(f, "expected {} version {}, found {}", FORMAT, VERSION, found),
+            VectorError::BadField(field) => write!(f, "missing or invalid field '{}'", field),
+            VectorError::UnknownEncoder(name) => write!(f, "unknown encoder '{}'", name),
+            VectorError::UnknownSource(name) => write!(f, "unknown source '{}'", name),
+            VectorError::UnknownDecoding(name) => write!(f, "unknown decoding '{}'", name),
+            VectorError::DifferentSet { field, expected, actual } => {
+                write!(f, "not the same vector set, {} is {} but expected {}", field, actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VectorError {}
+
+impl From<io::Error> for VectorError {
+    fn from(err: io::Error) -> Self {
+        VectorError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for VectorError {
+    fn from(err: serde_json::Error) -> Self {
+        VectorError::Json(err)
+    }
+}
+
+/// What a vector file covers.
+#[derive(Debug)]
+pub struct VectorSpec {
+    pub name: String,
+    pub encoder: Box<dyn FeatureEncoder>,
+    /// The salt is replaced by each of `salts` in turn.
+    pub config: LshConfig,
+    pub salts: Vec<u64>,
+    pub values: Vec<u64>,
+}
+
+impl VectorSpec {
+    /// Default salts and values for `scheme`, with at most `max_projections` projections.
+    pub fn for_scheme(scheme: &Scheme, max_projections: u64) -> Self {
+        VectorSpec {
+            name: scheme.name.to_string(),
+            encoder: encoder_by_name(scheme.encoder).expect("schemes only use built-in encoders"),
+            config: LshConfig { projection_count: scheme.config.projection_count.min(max_projections), ..scheme.config },
+            salts: DEFAULT_SALTS.to_vec(),
+            values: default_values(scheme.encoder).to_vec(),
+        }
+    }
+
+    /// The spec a vector file was generated from, to regenerate it.
+    pub fn from_json(json: &Value) -> Result<Self, VectorError> {
+        check_format(json)?;
+
+        let string = |field: &'static str| json[field].as_str().ok_or(VectorError::BadField(field));
+        let number = |field: &'static str| json[field].as_u64().ok_or(VectorError::BadField(field));
+
+        let encoder_name = string("encoder")?;
+        let source_name = string("source")?;
+        let decoding_name = string("decoding")?;
+
+        let groups = json["groups"].as_array().ok_or(VectorError::BadField("groups"))?;
+        let salts = groups.iter().map(|group| integer(&group["salt"]).ok_or(VectorError::BadField("salt"))).collect::<Result<Vec<_>, _>>()?;
+        let values = match groups.first() {
+            Some(group) => group["cases"]
+                .as_array()
+                .ok_or(VectorError::BadField("cases"))?
+                .iter()
+                .map(|case| integer(&case["value"]).ok_or(VectorError::BadField("value")))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(VectorSpec {
+            name: string("name")?.to_string(),
+            encoder: encoder_by_name(encoder_name).ok_or_else(|| VectorError::UnknownEncoder(encoder_name.to_string()))?,
+            config: LshConfig {
+                input_index_offset: number("input_index_offset")?,
+                source: source_by_name(source_name).ok_or_else(|| VectorError::UnknownSource(source_name.to_string()))?,
+                decoding: Decoding::from_name(decoding_name).ok_or_else(|| VectorError::UnknownDecoding(decoding_name.to_string()))?,
+                ..LshConfig::new(number("projection_count")?, Salt::test())
+            },
+            salts,
+            values,
+        })
+    }
+
+    /// Computes every value of the set with the Rust implementation, see the module docs.
+    pub fn generate(&self) -> Value {
+        let feature_count = self.encoder.dimension() as u64;
+
+        let groups: Vec<Value> = self
+            .salts
+            .iter()
+            .map(|&salt| {
+                let config = LshConfig { salt: Salt::recorded(salt, Fr::from(0u64)), ..self.config };
+                let matrix = ProjectionMatrix::new(&config, feature_count);
+
+                let table = |value: &dyn Fn(u64, u64) -> String| -> Value {
+                    (0..config.projection_count).map(|i| (0..feature_count).map(|j| value(i, j)).collect::<Vec<_>>()).collect()
+                };
+                let input_index = |j: u64| config.input_index_offset + j;
+
+                let poseidon = (config.source.name() == "poseidon")
+                    .then(|| table(&|i, j| BigUint::from_bytes_le(&poseidon_projection_bytes(salt, i, input_index(j))).to_string()));
+                let raw = table(&|i, j| config.source.projection(salt, i, input_index(j)).to_string());
+                let coefficients = table(&|i, j| matrix.get(i, j).to_string());
+
+                let cases: Vec<Value> = self.values.iter().map(|&value| case(value, &self.encoder.encode(value), &matrix)).collect();
+
+                json!({
+                    "salt": salt.to_string(),
+                    "poseidon": poseidon,
+                    "raw": raw,
+                    "coefficients": coefficients,
+                    "cases": cases,
+                })
+            })
+            .collect();
+
+        json!({
+            "format": FORMAT,
+            "version": VERSION,
+            "name": self.name,
+            "encoder": self.encoder.name(),
+            "source": self.config.source.name(),
+            "decoding": self.config.decoding.name(),
+            "input_index_offset": self.config.input_index_offset,
+            "projection_count": self.config.projection_count,
+            "feature_count": feature_count,
+            "groups": groups,
+        })
+    }
+
+    /// Writes the generated set to `dir/<name>.json`.
+    pub fn export(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(format!("{}.json", self.name)), format!("{}\n", self.generate()))
+    }
+}
+
+// features, sums and hash of one input value
+fn case(value: u64, features: &Features, matrix: &ProjectionMatrix) -> Value {
+    let (features_json, sums): (Vec<Value>, Vec<Value>) = match features {
+        Features::Integer(features) => (
+            features.iter().map(|feature| json!(feature.to_string())).collect(),
+            matrix
+                .rows()
+                .map(|row| json!(to_signed(&field_partial_sums(features, row).last().copied().unwrap_or_default()).to_string()))
+                .collect(),
+        ),
+        Features::Float(features) => (
+            features.iter().map(|&feature| json!(feature)).collect(),
+            matrix.rows().map(|row| json!(projection_sum_f64(features, row))).collect(),
+        ),
+    };
+
+    json!({
+        "value": value.to_string(),
+        "features": features_json,
+        "sums": sums,
+        "hash": matrix.hash(features).to_string(),
+    })
+}
+
+pub fn load(path: &Path) -> Result<Value, VectorError> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn check_format(json: &Value) -> Result<(), VectorError> {
+    if json["format"] != FORMAT || json["version"] != VERSION {
+        return Err(VectorError::Format(format!("{} version {}", json["format"], json["version"])));
+    }
+
+    Ok(())
+}
+
+// u64 from a decimal string or a JSON number
+fn integer(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str()?.parse().ok())
+}
+
+/// Checked and mismatched values of one of [`KINDS`].
+#[derive(Clone, Debug, Default)]
+pub struct KindCount {
+    pub name: &'static str,
+    pub checked: u64,
+    pub mismatched: u64,
+}
+
+/// Result of [`check_vectors`].
+#[derive(Clone, Debug)]
+pub struct ConformanceReport {
+    pub name: String,
+    pub kinds: Vec<KindCount>,
+    /// The first [`MAX_LISTED`] mismatches, `path: expected x, got y`.
+    pub mismatches: Vec<String>,
+}
+
+impl ConformanceReport {
+    /// Something was checked and nothing differs.
+    pub fn is_match(&self) -> bool {
+        self.kinds.iter().any(|kind| kind.checked > 0) && self.kinds.iter().all(|kind| kind.mismatched == 0)
+    }
+
+    fn record(&mut self, kind: &'static str, path: impl FnOnce() -> String, expected: &Value, actual: &Value) {
+        let count = self.kinds.iter_mut().find(|count| count.name == kind).unwrap();
+        count.checked += 1;
+
+        if !same_value(kind, expected, actual) {
+            count.mismatched += 1;
+
+            if self.mismatches.len() < MAX_LISTED {
+                self.mismatches.push(format!("{}: expected {}, got {}", path(), expected, actual));
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {}", self.name, if self.is_match() { "conforms" } else { "DOES NOT CONFORM" })?;
+
+        for kind in &self.kinds {
+            match kind.checked {
+                0 => writeln!(f, "  {:<13} not provided", kind.name)?,
+                _ => writeln!(f, "  {:<13} {:>8} checked {:>8} mismatched", kind.name, kind.checked, kind.mismatched)?,
+            }
+        }
+
+        for mismatch in &self.mismatches {
+            writeln!(f, "  {}", mismatch)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares an implementation's output against a vector file. `actual` has the same
+/// layout and may leave out anything it does not compute: missing kinds are reported as
+/// not provided, missing entries inside a provided array as mismatches. Signed values
+/// may also be given as field elements (upper half negative) and hashes as bit arrays,
+/// the way circom outputs them.
+pub fn check_vectors(expected: &Value, actual: &Value) -> Result<ConformanceReport, VectorError> {
+    check_format(expected)?;
+
+    for field in ["version", "encoder", "source", "decoding", "input_index_offset", "projection_count", "feature_count"] {
+        same_field(field.to_string(), &expected[field], &actual[field])?;
+    }
+
+    let mut report = ConformanceReport {
+        name: expected["name"].as_str().unwrap_or("unnamed").to_string(),
+        kinds: KINDS.iter().map(|&name| KindCount { name, ..Default::default() }).collect(),
+        mismatches: Vec::new(),
+    };
+
+    let expected_groups = expected["groups"].as_array().ok_or(VectorError::BadField("groups"))?;
+
+    for (g, (expected_group, actual_group)) in expected_groups.iter().zip(actual["groups"].as_array().into_iter().flatten()).enumerate() {
+        same_field(format!("groups[{}].salt", g), &expected_group["salt"], &actual_group["salt"])?;
+
+        for kind in MATRIX_KINDS {
+            let Some(actual_rows) = actual_group[kind].as_array() else {
+                continue;
+            };
+
+            for (i, expected_row) in expected_group[kind].as_array().into_iter().flatten().enumerate() {
+                for (j, expected_value) in expected_row.as_array().into_iter().flatten().enumerate() {
+                    let actual_value = actual_rows.get(i).and_then(|row| row.get(j)).unwrap_or(&Value::Null);
+                    report.record(kind, || format!("groups[{}].{}[{}][{}]", g, kind, i, j), expected_value, actual_value);
+                }
+            }
+        }
+
+        let actual_cases = actual_group["cases"].as_array().into_iter().flatten();
+
+        for (c, (expected_case, actual_case)) in expected_group["cases"].as_array().into_iter().flatten().zip(actual_cases).enumerate() {
+            same_field(format!("groups[{}].cases[{}].value", g, c), &expected_case["value"], &actual_case["value"])?;
+
+            for kind in ["features", "sums"] {
+                let Some(actual_values) = actual_case[kind].as_array() else {
+                    continue;
+                };
+
+                for (k, expected_value) in expected_case[kind].as_array().into_iter().flatten().enumerate() {
+                    let actual_value = actual_values.get(k).unwrap_or(&Value::Null);
+                    report.record(kind, || format!("groups[{}].cases[{}].{}[{}]", g, c, kind, k), expected_value, actual_value);
+                }
+            }
+
+            if !actual_case["hash"].is_null() {
+                report.record("hash", || format!("groups[{}].cases[{}].hash", g, c), &expected_case["hash"], &actual_case["hash"]);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+// header values have to match for the files to be comparable, absent ones are not checked
+fn same_field(field: String, expected: &Value, actual: &Value) -> Result<(), VectorError> {
+    if actual.is_null() || same_value("header", expected, actual) {
+        return Ok(());
+    }
+
+    Err(VectorError::DifferentSet { field, expected: expected.clone(), actual: actual.clone() })
+}
+
+fn same_value(kind: &str, expected: &Value, actual: &Value) -> bool {
+    if expected.is_f64() {
+        let actual = actual.as_f64().or_else(|| actual.as_str()?.parse().ok());
+        return actual == expected.as_f64();
+    }
+
+    match kind {
+        "hash" => Some(expected) == bit_string(actual).as_ref(),
+        "poseidon" => big_integer(expected).is_some() && big_integer(expected) == big_integer(actual),
+        "header" => expected == actual || (big_integer(expected).is_some() && big_integer(expected) == big_integer(actual)),
+        _ => big_integer(expected).is_some() && big_integer(expected).map(to_signed_integer) == big_integer(actual).map(to_signed_integer),
+    }
+}
+
+// '0'/'1' string from a string or an array of bits
+fn bit_string(value: &Value) -> Option<Value> {
+    match value {
+        Value::String(_) => Some(value.clone()),
+        Value::Array(bits) => bits
+            .iter()
+            .map(|bit| match integer(bit)? {
+                0 => Some('0'),
+                1 => Some('1'),
+                _ => None,
+            })
+            .collect::<Option<String>>()
+            .map(Value::String),
+        _ => None,
+    }
+}
+
+fn big_integer(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::String(value) => value.parse().ok(),
+        Value::Number(number) => number.as_i64().map(BigInt::from).or_else(|| number.as_u64().map(BigInt::from)),
+        _ => None,
+    }
+}
+
+// field elements in the upper half of the field are negative numbers
+fn to_signed_integer(value: BigInt) -> BigInt {
+    let modulus = BigInt::from(BigUint::from_bytes_le(&Fr::MODULUS.to_bytes_le()));
+
+    if value > modulus.clone() / 2 && value < modulus {
+        value - modulus
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every pinned file in `vectors/v1` still matches what the Rust implementation generates.
+    #[test]
+    fn pinned_vectors_conform() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("vectors/v1");
+        let mut paths: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).filter(|path| path.extension().is_some_and(|ext| ext == "json")).collect();
+        paths.sort();
+
+        assert!(!paths.is_empty(), "no vectors in {}", dir.display());
+
+        for path in paths {
+            let expected = load(&path).unwrap();
+            let report = check_vectors(&expected, &VectorSpec::from_json(&expected).unwrap().generate()).unwrap();
+
+            assert!(report.is_match(), "{}:\n{}", path.display(), report);
+        }
+    }
+}